@@ -0,0 +1,155 @@
+//! Why a unit is running
+use crate::{ActiveState, SystemCtl};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Reason a unit is active, see [SystemCtl::activation_source]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActivationSource {
+    /// Activated by socket, timer or path units (`TriggeredBy`)
+    Triggered {
+        /// Triggering units
+        by: Vec<String>,
+    },
+    /// Pulled in as a dependency of other units, usually targets
+    /// (`WantedBy`, `RequiredBy`)
+    PulledIn {
+        /// Depending units
+        by: Vec<String>,
+    },
+    /// Started explicitly: no unit triggers nor pulls it in
+    Manual,
+}
+
+impl ActivationSource {
+    /// Deduces the activation source from the `TriggeredBy` units,
+    /// then the `WantedBy` and `RequiredBy` ones
+    pub fn from_dependencies(triggered_by: &[String], pulled_in_by: &[String]) -> Self {
+        if !triggered_by.is_empty() {
+            Self::Triggered {
+                by: triggered_by.to_vec(),
+            }
+        } else if !pulled_in_by.is_empty() {
+            Self::PulledIn {
+                by: pulled_in_by.to_vec(),
+            }
+        } else {
+            Self::Manual
+        }
+    }
+
+    /// Units responsible for the activation, empty for [ActivationSource::Manual]
+    pub fn units(&self) -> &[String] {
+        match self {
+            Self::Triggered { by } | Self::PulledIn { by } => by,
+            Self::Manual => &[],
+        }
+    }
+}
+
+impl SystemCtl {
+    /// Returns why given `unit` is running, `None` if it is not active.
+    /// Unlike [crate::Unit::activation_source], only the triggering
+    /// and depending units that are active themselves are considered.
+    pub fn activation_source(
+        &self,
+        unit: impl AsRef<str>,
+    ) -> std::io::Result<Option<ActivationSource>> {
+        let properties = self.show(
+            unit.as_ref(),
+            &["ActiveState", "TriggeredBy", "WantedBy", "RequiredBy"],
+        )?;
+        let state = properties.get("ActiveState").map(String::as_str);
+        if !matches!(state.map(str::parse), Some(Ok(state)) if is_running(state)) {
+            return Ok(None);
+        }
+        let active = |property: &str| -> std::io::Result<Vec<String>> {
+            let mut units = Vec::new();
+            for unit in properties
+                .get(property)
+                .into_iter()
+                .flat_map(|v| v.split_ascii_whitespace())
+            {
+                if self.is_active(unit)? {
+                    units.push(unit.to_string());
+                }
+            }
+            Ok(units)
+        };
+        let triggered_by = active("TriggeredBy")?;
+        let mut pulled_in_by = active("WantedBy")?;
+        pulled_in_by.extend(active("RequiredBy")?);
+        Ok(Some(ActivationSource::from_dependencies(
+            &triggered_by,
+            &pulled_in_by,
+        )))
+    }
+}
+
+/// Returns `true` if a unit in given state has been activated
+pub(crate) fn is_running(state: ActiveState) -> bool {
+    matches!(
+        state,
+        ActiveState::Active
+            | ActiveState::Activating
+            | ActiveState::Reloading
+            | ActiveState::Refreshing
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_activation_source() {
+        let units =
+            |names: &[&str]| -> Vec<String> { names.iter().map(|n| n.to_string()).collect() };
+        let source = ActivationSource::from_dependencies(
+            &units(&["cron.timer"]),
+            &units(&["multi-user.target"]),
+        );
+        assert_eq!(
+            source,
+            ActivationSource::Triggered {
+                by: units(&["cron.timer"])
+            }
+        );
+        let source = ActivationSource::from_dependencies(&[], &units(&["multi-user.target"]));
+        assert_eq!(source.units(), units(&["multi-user.target"]));
+        assert_eq!(
+            ActivationSource::from_dependencies(&[], &[]),
+            ActivationSource::Manual
+        );
+
+        // foo.service is triggered by an inactive socket
+        // and wanted by an active target
+        let mock = MockExecutor::new();
+        let inactive = MockResponse {
+            exit_code: 3,
+            stdout: "inactive\n".into(),
+            ..Default::default()
+        };
+        mock.on(
+            &["show"],
+            MockResponse::success(
+                "ActiveState=active\nTriggeredBy=foo.socket\nWantedBy=multi-user.target\n",
+            ),
+        )
+        .on(&["is-active"], inactive)
+        .on(
+            &["is-active", "multi-user.target"],
+            MockResponse::success("active\n"),
+        );
+        let ctl = mock.ctl();
+        assert_eq!(
+            ctl.activation_source("foo.service").unwrap(),
+            Some(ActivationSource::PulledIn {
+                by: units(&["multi-user.target"])
+            })
+        );
+    }
+}
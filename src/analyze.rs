@@ -0,0 +1,157 @@
+//! Condition evaluation through `systemd-analyze`
+use crate::SystemCtl;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const SYSTEMD_ANALYZE_PATH: &str = "/usr/bin/systemd-analyze";
+
+/// Outcome of a single condition or assertion
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConditionCheck {
+    /// Tested expression, like `ConditionPathExists=/etc/foo`
+    pub condition: String,
+    /// `true` if this condition holds on the host
+    pub passed: bool,
+}
+
+/// Outcome of `systemd-analyze condition`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ConditionResult {
+    /// `true` if the unit would be started on this host:
+    /// all assertions and conditions passed, triggering
+    /// conditions (`|`) being combined like systemd does
+    pub passed: bool,
+    /// Outcome of each tested expression
+    pub checks: Vec<ConditionCheck>,
+}
+
+impl ConditionResult {
+    /// Parses the output of `systemd-analyze condition`
+    pub fn parse(content: &str, passed: bool) -> Self {
+        let checks = content
+            .lines()
+            .filter_map(|line| {
+                let line = line.trim().trim_end_matches('.');
+                // unit name prefix is optionnal
+                let line = match line.split_once(": ") {
+                    Some((_, line)) => line,
+                    None => line,
+                };
+                if let Some(condition) = line.strip_suffix(" succeeded") {
+                    Some((condition, true))
+                } else {
+                    line.strip_suffix(" failed")
+                        .map(|condition| (condition, false))
+                }
+            })
+            .filter(|(condition, _)| condition.contains('='))
+            .map(|(condition, passed)| ConditionCheck {
+                condition: condition.to_string(),
+                passed,
+            })
+            .collect();
+        Self { passed, checks }
+    }
+}
+
+impl SystemCtl {
+    /// Evaluates `Condition*=`/`Assert*=` expressions on the targeted
+    /// host or container (`systemd-analyze condition`), to know whether
+    /// a unit relying on them would be started, before installing it.
+    ///
+    /// ```no_run
+    /// let ctl = systemctl::SystemCtl::default();
+    /// let result = ctl.analyze_condition(&["ConditionPathExists=/etc/foo"])
+    ///     .unwrap();
+    /// println!("would start: {}", result.passed);
+    /// ```
+    pub fn analyze_condition(&self, conditions: &[&str]) -> std::io::Result<ConditionResult> {
+        let mut command = std::process::Command::new(SYSTEMD_ANALYZE_PATH);
+        command
+            .args(self.target_args())
+            .arg("condition")
+            .args(conditions);
+        self.customize(&mut command);
+        let output = self.execute(command, self.command_timeout)?;
+        // verdicts are logged to stderr
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        let content = format!("{}{}", stdout, stderr);
+        let result = ConditionResult::parse(&content, output.status.success());
+        if result.checks.is_empty() && !output.status.success() {
+            // invalid expression
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("systemd-analyze condition failed: {}", stderr.trim()),
+            ));
+        }
+        Ok(result)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_parse_condition_result() {
+        let content = "\
+test.service: AssertPathExists=/etc succeeded.
+Asserts succeeded.
+test.service: ConditionPathExists=/etc/foo failed.
+test.service: ConditionArchitecture=x86-64 succeeded.
+Conditions failed.";
+        let result = ConditionResult::parse(content, false);
+        assert!(!result.passed);
+        assert_eq!(
+            result.checks,
+            vec![
+                ConditionCheck {
+                    condition: "AssertPathExists=/etc".into(),
+                    passed: true,
+                },
+                ConditionCheck {
+                    condition: "ConditionPathExists=/etc/foo".into(),
+                    passed: false,
+                },
+                ConditionCheck {
+                    condition: "ConditionArchitecture=x86-64".into(),
+                    passed: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_analyze_condition() {
+        let mock = MockExecutor::new();
+        mock.on(
+            &["condition"],
+            MockResponse::failure(1, "ConditionPathExists=!/ failed.\nConditions failed.\n"),
+        );
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .host("root@web1".to_string())
+            .executor(std::sync::Arc::new(mock.clone()))
+            .build();
+        let result = ctl.analyze_condition(&["ConditionPathExists=!/"]).unwrap();
+        assert!(!result.passed);
+        assert_eq!(
+            result.checks,
+            vec![ConditionCheck {
+                condition: "ConditionPathExists=!/".into(),
+                passed: false,
+            }]
+        );
+        let calls = mock.calls();
+        assert_eq!(calls[0].program, SYSTEMD_ANALYZE_PATH);
+        assert_eq!(
+            calls[0].args,
+            ["-H", "root@web1", "condition", "ConditionPathExists=!/"]
+        );
+    }
+}
@@ -0,0 +1,170 @@
+//! Interactive authentication detection and password agent queries
+use crate::SystemCtl;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Directory where pending `systemd-ask-password` queries are published
+const ASK_PASSWORD_DIR: &str = "/run/systemd/ask-password";
+
+/// Error payload returned (as [std::io::ErrorKind::PermissionDenied])
+/// when an operation failed because it requires interactive authentication,
+/// typically when using `--no-ask-password`.
+///
+/// ```no_run
+/// let ctl = systemctl::SystemCtl::builder()
+///     .additional_args(vec![])
+///     .no_ask_password(true)
+///     .build();
+/// if let Err(e) = ctl.restart("cron.service") {
+///     if let Some(e) = e.get_ref().and_then(|e| e.downcast_ref::<systemctl::PasswordRequired>()) {
+///         println!("authentication required: {}", e.message);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct PasswordRequired {
+    /// Error message reported by systemctl
+    pub message: String,
+    /// Password queries pending at the time of failure
+    pub pending: Vec<AskPasswordQuery>,
+}
+
+impl PasswordRequired {
+    /// Builds self, collecting the queries pending right now on the
+    /// target of `ctl`, none if they cannot be listed
+    pub(crate) fn new(ctl: &SystemCtl, message: &str) -> Self {
+        Self {
+            message: message.to_string(),
+            pending: ctl.pending_password_queries().unwrap_or_default(),
+        }
+    }
+
+    /// Returns `true` if given stderr output reports missing interactive authentication
+    pub(crate) fn matches(stderr: &str) -> bool {
+        stderr.contains("Interactive authentication required")
+    }
+}
+
+impl std::fmt::Display for PasswordRequired {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl std::error::Error for PasswordRequired {}
+
+/// A pending `systemd-ask-password` query
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct AskPasswordQuery {
+    /// Query identifier, like `cryptsetup:/dev/sda2`
+    pub id: Option<String>,
+    /// Message presented to the user
+    pub message: Option<String>,
+    /// PID of the requesting process
+    pub pid: Option<u64>,
+    /// Socket the password should be sent to
+    pub socket: Option<String>,
+}
+
+impl AskPasswordQuery {
+    /// Parses the content of an `ask.*` query file
+    fn parse(content: &str) -> Self {
+        let mut query = Self::default();
+        for (k, v) in content.lines().filter_map(|line| line.split_once('=')) {
+            let val = v.trim().to_string();
+            match k.trim() {
+                "Id" => query.id = Some(val),
+                "Message" => query.message = Some(val),
+                "PID" => query.pid = val.parse().ok(),
+                "Socket" => query.socket = Some(val),
+                _ => {},
+            }
+        }
+        query
+    }
+}
+
+impl SystemCtl {
+    /// Lists the password queries currently pending on the local host.
+    /// Returns an empty list when no agent directory exists.
+    /// Queries of a remote `host` or `machine` cannot be listed,
+    /// this fails with [ErrorKind::Unsupported] when one is set.
+    pub fn pending_password_queries(&self) -> std::io::Result<Vec<AskPasswordQuery>> {
+        if self.host.is_some() || self.machine.is_some() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "password queries can only be listed on the local host",
+            ));
+        }
+        let dir = Path::new(ASK_PASSWORD_DIR);
+        if !dir.is_dir() {
+            return Ok(Vec::new());
+        }
+        let mut queries = Vec::new();
+        for entry in std::fs::read_dir(dir)? {
+            let entry = entry?;
+            if !entry.file_name().to_string_lossy().starts_with("ask.") {
+                continue;
+            }
+            if let Ok(content) = std::fs::read_to_string(entry.path()) {
+                queries.push(AskPasswordQuery::parse(&content));
+            }
+        }
+        Ok(queries)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_ask_password_query() {
+        let content = "\
+[Ask]
+PID=1234
+Socket=/run/systemd/ask-password/sck.4f1c
+AcceptCached=1
+Echo=0
+NotAfter=0
+Message=Please enter passphrase for disk root
+Id=cryptsetup:/dev/sda2";
+        let query = AskPasswordQuery::parse(content);
+        assert_eq!(query.id.as_deref(), Some("cryptsetup:/dev/sda2"));
+        assert_eq!(
+            query.message.as_deref(),
+            Some("Please enter passphrase for disk root")
+        );
+        assert_eq!(query.pid, Some(1234));
+        assert_eq!(
+            query.socket.as_deref(),
+            Some("/run/systemd/ask-password/sck.4f1c")
+        );
+    }
+
+    #[test]
+    fn test_password_required_detection() {
+        assert!(PasswordRequired::matches(
+            "Failed to restart cron.service: Interactive authentication required.\n"
+        ));
+        assert!(!PasswordRequired::matches(
+            "Failed to restart foo.service: Unit foo.service not found.\n"
+        ));
+    }
+
+    #[test]
+    fn test_remote_password_queries() {
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .machine("debian".to_string())
+            .build();
+        let e = ctl.pending_password_queries().unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Unsupported);
+        let e = PasswordRequired::new(&ctl, "Interactive authentication required.");
+        assert!(e.pending.is_empty());
+    }
+}
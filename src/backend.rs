@@ -0,0 +1,40 @@
+//! Alternate ways of reaching the service manager
+use crate::{RunResult, UnitList};
+use std::collections::HashMap;
+
+/// Serves the most frequent [SystemCtl](crate::SystemCtl) operations
+/// without spawning `systemctl`, see [SystemCtlBuilder::backend](crate::SystemCtlBuilder::backend).
+///
+/// Operations that are not part of this trait keep invoking `systemctl`.
+/// Jobs (start, stop..) are waited for, like `systemctl` does.
+/// Implementations return an error for failed operations, and
+/// a successful [RunResult] with empty outputs otherwise.
+pub trait Backend: std::fmt::Debug + Send + Sync {
+    /// Starts given `unit`
+    fn start(&self, unit: &str) -> std::io::Result<RunResult>;
+
+    /// Stops given `unit`
+    fn stop(&self, unit: &str) -> std::io::Result<RunResult>;
+
+    /// Restarts given `unit`
+    fn restart(&self, unit: &str) -> std::io::Result<RunResult>;
+
+    /// Reloads given `unit`
+    fn reload(&self, unit: &str) -> std::io::Result<RunResult>;
+
+    /// Returns `true` if given `unit` is active
+    fn is_active(&self, unit: &str) -> std::io::Result<bool>;
+
+    /// Returns the requested `properties` of given `unit`, formatted
+    /// like `systemctl show --timestamp=utc` does.
+    /// All properties are returned when `properties` is empty.
+    fn show(&self, unit: &str, properties: &[&str]) -> std::io::Result<HashMap<String, String>>;
+
+    /// Lists unit files, like `systemctl list-unit-files`
+    fn list_unit_files(
+        &self,
+        type_filter: Option<&str>,
+        state_filter: Option<&str>,
+        glob: Option<&str>,
+    ) -> std::io::Result<Vec<UnitList>>;
+}
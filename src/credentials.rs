@@ -0,0 +1,243 @@
+//! Service credentials inspection
+use crate::SystemCtl;
+use std::io::{Error, ErrorKind};
+use std::path::Path;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Directory where systemd exposes the credentials of running units
+const CREDENTIALS_DIR: &str = "/run/credentials";
+
+/// Describes where a credential comes from
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum CredentialSource {
+    /// `LoadCredential=` / `LoadCredentialEncrypted=`: read from a file or socket.
+    /// When no path is given, the credential is searched in the credential stores.
+    Load {
+        path: Option<String>,
+        encrypted: bool,
+    },
+    /// `SetCredential=` / `SetCredentialEncrypted=`: value is embedded in the unit
+    Set { value: String, encrypted: bool },
+    /// `ImportCredential=`: credentials matching a glob are imported
+    /// from the system credentials
+    Import,
+}
+
+/// A credential directive of a unit
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Credential {
+    /// Credential name, or glob for [CredentialSource::Import]
+    pub name: String,
+    /// Credential origin
+    pub source: CredentialSource,
+}
+
+impl Credential {
+    /// Parses the credential directives of a unit file, drop-ins included,
+    /// like the output of `systemctl cat $unit`.
+    /// An empty assignment resets the directives of that kind, like systemd does.
+    pub fn parse_unit_file(content: &str) -> Vec<Credential> {
+        let mut credentials: Vec<Credential> = Vec::new();
+        for (key, value) in content
+            .lines()
+            .filter_map(|line| line.trim().split_once('='))
+        {
+            let value = value.trim();
+            let source = match key.trim() {
+                "LoadCredential" => CredentialSource::Load {
+                    path: None,
+                    encrypted: false,
+                },
+                "LoadCredentialEncrypted" => CredentialSource::Load {
+                    path: None,
+                    encrypted: true,
+                },
+                "SetCredential" => CredentialSource::Set {
+                    value: String::new(),
+                    encrypted: false,
+                },
+                "SetCredentialEncrypted" => CredentialSource::Set {
+                    value: String::new(),
+                    encrypted: true,
+                },
+                "ImportCredential" => CredentialSource::Import,
+                _ => continue,
+            };
+            if value.is_empty() {
+                // encrypted and plain directives share the same list
+                credentials.retain(|c| {
+                    std::mem::discriminant(&c.source) != std::mem::discriminant(&source)
+                });
+                continue;
+            }
+            let (name, argument) = match value.split_once(':') {
+                Some((name, argument)) => (name, Some(argument.to_string())),
+                None => (value, None),
+            };
+            let source = match source {
+                CredentialSource::Load { encrypted, .. } => CredentialSource::Load {
+                    path: argument,
+                    encrypted,
+                },
+                CredentialSource::Set { encrypted, .. } => CredentialSource::Set {
+                    value: argument.unwrap_or_default(),
+                    encrypted,
+                },
+                CredentialSource::Import => CredentialSource::Import,
+            };
+            credentials.push(Credential {
+                // globs are not split
+                name: if source == CredentialSource::Import {
+                    value.to_string()
+                } else {
+                    name.to_string()
+                },
+                source,
+            });
+        }
+        credentials
+    }
+
+    /// `true` if this credential is encrypted
+    pub fn is_encrypted(&self) -> bool {
+        matches!(
+            self.source,
+            CredentialSource::Load {
+                encrypted: true,
+                ..
+            } | CredentialSource::Set {
+                encrypted: true,
+                ..
+            }
+        )
+    }
+}
+
+/// Credentials of a unit
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Credentials {
+    /// Credential directives declared by the unit
+    pub directives: Vec<Credential>,
+    /// Names of the credentials currently passed to the running unit
+    /// (`/run/credentials/$unit`), `None` if the unit is not running
+    /// or this directory is not readable
+    pub runtime: Option<Vec<String>>,
+}
+
+impl SystemCtl {
+    /// Returns the credentials declared by given `unit` (`LoadCredential=`,
+    /// `SetCredential=`, `ImportCredential=`..) and the ones currently
+    /// passed to it. `unit` must be a complete name, like `foo.service`.
+    /// The runtime directory is only readable with sufficient privileges,
+    /// and only on the local host: this fails with [ErrorKind::Unsupported]
+    /// when a `host` or `machine` is set.
+    pub fn credentials(&self, unit: &str) -> std::io::Result<Credentials> {
+        if self.host.is_some() || self.machine.is_some() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "credentials can only be inspected on the local host",
+            ));
+        }
+        let content = self.cat(unit)?;
+        let runtime_dir = Path::new(CREDENTIALS_DIR).join(unit);
+        let runtime = std::fs::read_dir(runtime_dir).ok().map(|entries| {
+            let mut names: Vec<String> = entries
+                .filter_map(|entry| entry.ok())
+                .map(|entry| entry.file_name().to_string_lossy().into_owned())
+                .collect();
+            names.sort();
+            names
+        });
+        Ok(Credentials {
+            directives: Credential::parse_unit_file(&content.stdout),
+            runtime,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_credentials() {
+        let content = "\
+# /etc/systemd/system/app.service
+[Service]
+ExecStart=/usr/bin/app
+LoadCredential=tls.key:/etc/app/tls.key
+LoadCredential=token
+LoadCredentialEncrypted=db:/etc/credstore.encrypted/db
+SetCredential=user:admin:42
+ImportCredential=app.*
+
+# /etc/systemd/system/app.service.d/override.conf
+[Service]
+SetCredentialEncrypted=
+LoadCredential=tls.crt:/etc/app/tls.crt";
+        let credentials = Credential::parse_unit_file(content);
+        assert_eq!(
+            credentials,
+            vec![
+                Credential {
+                    name: "tls.key".into(),
+                    source: CredentialSource::Load {
+                        path: Some("/etc/app/tls.key".into()),
+                        encrypted: false
+                    },
+                },
+                Credential {
+                    name: "token".into(),
+                    source: CredentialSource::Load {
+                        path: None,
+                        encrypted: false
+                    },
+                },
+                Credential {
+                    name: "db".into(),
+                    source: CredentialSource::Load {
+                        path: Some("/etc/credstore.encrypted/db".into()),
+                        encrypted: true
+                    },
+                },
+                Credential {
+                    name: "app.*".into(),
+                    source: CredentialSource::Import,
+                },
+                Credential {
+                    name: "tls.crt".into(),
+                    source: CredentialSource::Load {
+                        path: Some("/etc/app/tls.crt".into()),
+                        encrypted: false
+                    },
+                },
+            ]
+        );
+        assert!(credentials[2].is_encrypted());
+        assert!(!credentials[0].is_encrypted());
+
+        let set = Credential::parse_unit_file("SetCredential=user:admin:42");
+        assert_eq!(
+            set[0].source,
+            CredentialSource::Set {
+                value: "admin:42".into(),
+                encrypted: false
+            }
+        );
+    }
+
+    #[test]
+    fn test_remote_credentials() {
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .host("root@web1".to_string())
+            .build();
+        let e = ctl.credentials("app.service").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Unsupported);
+    }
+}
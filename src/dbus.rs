@@ -0,0 +1,340 @@
+//! [Backend] talking to `org.freedesktop.systemd1` over D-Bus
+use crate::{format_utc_timestamp, Backend, RunResult, UnitList};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::os::unix::process::ExitStatusExt;
+use std::process::ExitStatus;
+use std::time::{Duration, UNIX_EPOCH};
+use zbus::blocking::{Connection, Proxy};
+use zbus::zvariant::{OwnedObjectPath, OwnedValue, Value};
+
+const DESTINATION: &str = "org.freedesktop.systemd1";
+const MANAGER_PATH: &str = "/org/freedesktop/systemd1";
+const MANAGER_INTERFACE: &str = "org.freedesktop.systemd1.Manager";
+const UNIT_INTERFACE: &str = "org.freedesktop.systemd1.Unit";
+const PROPERTIES_INTERFACE: &str = "org.freedesktop.DBus.Properties";
+
+/// [Backend] calling the service manager directly over D-Bus,
+/// saving one process per operation.
+///
+/// Only the manager of the connection can be reached: [SystemCtl](crate::SystemCtl)
+/// handles targeting a `host`, a `machine` or the `--user` manager reject
+/// this backend with an [std::io::ErrorKind::Unsupported] error.
+/// Use [DbusBackend::session] to reach the user manager.
+///
+/// ```no_run
+/// use std::sync::Arc;
+/// use systemctl::{DbusBackend, SystemCtl};
+///
+/// let ctl = SystemCtl::builder()
+///     .additional_args(vec![])
+///     .backend(Arc::new(DbusBackend::system().unwrap()))
+///     .build();
+/// println!("active: {}", ctl.is_active("cron.service").unwrap());
+/// ```
+#[derive(Clone, Debug)]
+pub struct DbusBackend {
+    connection: Connection,
+}
+
+impl DbusBackend {
+    /// Connects to the system service manager
+    pub fn system() -> std::io::Result<Self> {
+        Ok(Self::from_connection(
+            Connection::system().map_err(to_io_error)?,
+        ))
+    }
+
+    /// Connects to the service manager of the calling user, like `--user`
+    pub fn session() -> std::io::Result<Self> {
+        Ok(Self::from_connection(
+            Connection::session().map_err(to_io_error)?,
+        ))
+    }
+
+    /// Uses an existing bus connection
+    pub fn from_connection(connection: Connection) -> Self {
+        Self { connection }
+    }
+
+    /// Invokes a method of the manager object
+    fn call_manager<B, T>(&self, method: &str, body: &B) -> std::io::Result<T>
+    where
+        B: serde::Serialize + zbus::zvariant::DynamicType,
+        T: for<'de> serde::Deserialize<'de> + zbus::zvariant::Type,
+    {
+        self.connection
+            .call_method(
+                Some(DESTINATION),
+                MANAGER_PATH,
+                Some(MANAGER_INTERFACE),
+                method,
+                body,
+            )
+            .and_then(|reply| reply.body().deserialize::<T>())
+            .map_err(to_io_error)
+    }
+
+    /// Returns the object path of given unit, loading it if needed
+    fn unit_path(&self, unit: &str) -> std::io::Result<OwnedObjectPath> {
+        self.call_manager("LoadUnit", &(unit,))
+    }
+
+    /// Returns all properties of `interface` on object `path`
+    fn get_all(
+        &self,
+        path: &OwnedObjectPath,
+        interface: &str,
+    ) -> std::io::Result<HashMap<String, OwnedValue>> {
+        self.connection
+            .call_method(
+                Some(DESTINATION),
+                path,
+                Some(PROPERTIES_INTERFACE),
+                "GetAll",
+                &(interface,),
+            )
+            .and_then(|reply| reply.body().deserialize())
+            .map_err(to_io_error)
+    }
+
+    /// Runs a start/stop.. job, replacing conflicting jobs, and waits
+    /// for its completion like `systemctl` does. Jobs not completing
+    /// with the `done` result are reported as errors.
+    fn job(&self, method: &str, unit: &str) -> std::io::Result<RunResult> {
+        let manager = Proxy::new(
+            &self.connection,
+            DESTINATION,
+            MANAGER_PATH,
+            MANAGER_INTERFACE,
+        )
+        .map_err(to_io_error)?;
+        // listening before the job is queued, so that its removal is not missed
+        let mut removed = manager.receive_signal("JobRemoved").map_err(to_io_error)?;
+        match manager.call::<_, _, ()>("Subscribe", &()) {
+            Err(zbus::Error::MethodError(name, _, _))
+                if name.as_str() == "org.freedesktop.systemd1.AlreadySubscribed" => {},
+            result => result.map_err(to_io_error)?,
+        }
+        let job: OwnedObjectPath = manager
+            .call(method, &(unit, "replace"))
+            .map_err(to_io_error)?;
+        let result = loop {
+            let signal = removed
+                .next()
+                .ok_or_else(|| Error::new(ErrorKind::BrokenPipe, "bus connection closed"))?;
+            let (_id, path, _unit, result): (u32, OwnedObjectPath, String, String) =
+                signal.body().deserialize().map_err(to_io_error)?;
+            if path == job {
+                break result;
+            }
+        };
+        if result != "done" {
+            return Err(Error::other(format!(
+                "job for {} failed with result '{}'",
+                unit, result
+            )));
+        }
+        Ok(RunResult {
+            stdout: String::new(),
+            stderr: String::new(),
+            exit_status: ExitStatus::from_raw(0),
+            warnings: Vec::new(),
+        })
+    }
+}
+
+impl Backend for DbusBackend {
+    fn start(&self, unit: &str) -> std::io::Result<RunResult> {
+        self.job("StartUnit", unit)
+    }
+
+    fn stop(&self, unit: &str) -> std::io::Result<RunResult> {
+        self.job("StopUnit", unit)
+    }
+
+    fn restart(&self, unit: &str) -> std::io::Result<RunResult> {
+        self.job("RestartUnit", unit)
+    }
+
+    fn reload(&self, unit: &str) -> std::io::Result<RunResult> {
+        self.job("ReloadUnit", unit)
+    }
+
+    fn is_active(&self, unit: &str) -> std::io::Result<bool> {
+        let properties = self.show(unit, &["ActiveState"])?;
+        Ok(properties.get("ActiveState").map(String::as_str) == Some("active"))
+    }
+
+    fn show(&self, unit: &str, properties: &[&str]) -> std::io::Result<HashMap<String, String>> {
+        let path = self.unit_path(unit)?;
+        let mut values = self.get_all(&path, UNIT_INTERFACE)?;
+        // type specific properties: `org.freedesktop.systemd1.Service`..
+        if let Some((_, suffix)) = unit.rsplit_once('.') {
+            let mut chars = suffix.chars();
+            if let Some(first) = chars.next() {
+                let interface = format!(
+                    "org.freedesktop.systemd1.{}{}",
+                    first.to_ascii_uppercase(),
+                    chars.as_str()
+                );
+                if let Ok(specific) = self.get_all(&path, &interface) {
+                    values.extend(specific);
+                }
+            }
+        }
+        Ok(values
+            .iter()
+            .filter(|(name, _)| properties.is_empty() || properties.contains(&name.as_str()))
+            .map(|(name, value)| (name.clone(), format_value(name, value)))
+            .collect())
+    }
+
+    fn list_unit_files(
+        &self,
+        type_filter: Option<&str>,
+        state_filter: Option<&str>,
+        glob: Option<&str>,
+    ) -> std::io::Result<Vec<UnitList>> {
+        let states: Vec<&str> = state_filter.into_iter().collect();
+        let patterns: Vec<&str> = glob.into_iter().collect();
+        let files: Vec<(String, String)> =
+            self.call_manager("ListUnitFilesByPatterns", &(states, patterns))?;
+        let mut units: Vec<UnitList> = files
+            .into_iter()
+            .filter_map(|(path, state)| {
+                let unit_file = path.rsplit('/').next()?.to_string();
+                if let Some(utype) = type_filter {
+                    if !unit_file.ends_with(&format!(".{}", utype)) {
+                        return None;
+                    }
+                }
+                Some(UnitList {
+                    unit_file,
+                    state,
+                    // not exposed by the listing
+                    vendor_preset: None,
+                })
+            })
+            .collect();
+        units.sort_by(|a, b| a.unit_file.cmp(&b.unit_file));
+        Ok(units)
+    }
+}
+
+/// Formats a property value like `systemctl show --timestamp=utc` does
+fn format_value(name: &str, value: &Value) -> String {
+    match value {
+        Value::Bool(b) => if *b { "yes" } else { "no" }.to_string(),
+        Value::U64(u64::MAX) if name.ends_with("USec") => "infinity".to_string(),
+        Value::U64(usec) if name.ends_with("USec") => format!("{}us", usec),
+        Value::U64(u64::MAX) => "[not set]".to_string(),
+        Value::U64(0) if is_timestamp(name) => String::new(),
+        Value::U64(usec) if is_timestamp(name) => {
+            format_utc_timestamp(UNIX_EPOCH + Duration::from_micros(*usec))
+        },
+        Value::U8(v) => v.to_string(),
+        Value::I16(v) => v.to_string(),
+        Value::U16(v) => v.to_string(),
+        Value::I32(v) => v.to_string(),
+        Value::U32(v) => v.to_string(),
+        Value::I64(v) => v.to_string(),
+        Value::U64(v) => v.to_string(),
+        Value::F64(v) => v.to_string(),
+        Value::Str(s) => s.to_string(),
+        Value::ObjectPath(path) => path.to_string(),
+        Value::Value(inner) => format_value(name, inner),
+        Value::Array(items) if name.starts_with("Exec") => items
+            .iter()
+            .filter_map(format_exec_command)
+            .collect::<Vec<_>>()
+            .join(" "),
+        Value::Array(items) => items
+            .iter()
+            .map(|item| format_value(name, item))
+            .collect::<Vec<_>>()
+            .join(" "),
+        _ => String::new(),
+    }
+}
+
+/// `*Timestamp` and `*USecRealtime` properties are printed as dates
+fn is_timestamp(name: &str) -> bool {
+    name.ends_with("Timestamp") || name.ends_with("USecRealtime")
+}
+
+/// Formats an `Exec*` entry, `(path, argv, ignore_errors, ...)`
+fn format_exec_command(value: &Value) -> Option<String> {
+    let Value::Structure(fields) = value else {
+        return None;
+    };
+    let fields = fields.fields();
+    let (Some(Value::Str(path)), Some(Value::Array(argv))) = (fields.first(), fields.get(1)) else {
+        return None;
+    };
+    let argv: Vec<String> = argv.iter().map(|arg| format_value("", arg)).collect();
+    let ignore_errors = matches!(fields.get(2), Some(Value::Bool(true)));
+    Some(format!(
+        "{{ path={} ; argv[]={} ; ignore_errors={} }}",
+        path,
+        argv.join(" "),
+        if ignore_errors { "yes" } else { "no" }
+    ))
+}
+
+fn to_io_error(e: zbus::Error) -> Error {
+    let kind = match &e {
+        zbus::Error::MethodError(name, _, _) => match name.as_str() {
+            "org.freedesktop.systemd1.NoSuchUnit" | "org.freedesktop.systemd1.LoadFailed" => {
+                ErrorKind::NotFound
+            },
+            "org.freedesktop.DBus.Error.AccessDenied"
+            | "org.freedesktop.DBus.Error.InteractiveAuthorizationRequired" => {
+                ErrorKind::PermissionDenied
+            },
+            _ => ErrorKind::Other,
+        },
+        zbus::Error::InputOutput(e) => e.kind(),
+        _ => ErrorKind::Other,
+    };
+    Error::new(kind, e)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use zbus::zvariant::Array;
+
+    #[test]
+    fn test_format_value() {
+        assert_eq!(format_value("Transient", &Value::Bool(true)), "yes");
+        assert_eq!(format_value("MainPID", &Value::U32(787)), "787");
+        assert_eq!(
+            format_value("MemoryCurrent", &Value::U64(u64::MAX)),
+            "[not set]"
+        );
+        assert_eq!(
+            format_value("StateChangeTimestamp", &Value::U64(1704967200000000)),
+            "Thu 2024-01-11 10:00:00 UTC"
+        );
+        assert_eq!(format_value("StateChangeTimestamp", &Value::U64(0)), "");
+        assert_eq!(
+            format_value("StartLimitIntervalUSec", &Value::U64(10000000)),
+            "10000000us"
+        );
+        let after = Value::Array(Array::from(vec!["basic.target", "network.target"]));
+        assert_eq!(format_value("After", &after), "basic.target network.target");
+
+        let exec = ("/usr/sbin/cron", vec!["/usr/sbin/cron", "-f"], false);
+        let exec_start = Value::from(vec![exec]);
+        let formatted = format_value("ExecStart", &exec_start);
+        assert_eq!(
+            formatted,
+            "{ path=/usr/sbin/cron ; argv[]=/usr/sbin/cron -f ; ignore_errors=no }"
+        );
+        assert_eq!(
+            crate::exec_command_line(&formatted).as_deref(),
+            Some("/usr/sbin/cron -f")
+        );
+    }
+}
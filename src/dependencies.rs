@@ -0,0 +1,262 @@
+//! Dependency queries built on `systemctl list-dependencies`
+use crate::SystemCtl;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Kind of dependencies to list
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum DependencyKind {
+    /// Requirement dependencies (`Requires=`, `Wants=`..)
+    #[default]
+    Requirement,
+    /// Units the queried unit is ordered after (`--after`)
+    After,
+    /// Units the queried unit is ordered before (`--before`)
+    Before,
+}
+
+/// A unit and its dependencies, as a tree
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DependencyNode {
+    /// Unit name
+    pub unit: String,
+    /// Direct dependencies of this unit
+    pub children: Vec<DependencyNode>,
+}
+
+impl DependencyNode {
+    /// Iterates over all transitive dependencies, depth first,
+    /// self excluded. A unit reachable through several paths
+    /// is visited several times.
+    pub fn iter(&self) -> DependencyIter<'_> {
+        DependencyIter {
+            stack: self.children.iter().rev().collect(),
+        }
+    }
+
+    /// Returns the names of all transitive dependencies,
+    /// without duplicates, in depth first order
+    pub fn units(&self) -> Vec<&str> {
+        let mut units: Vec<&str> = Vec::new();
+        for node in self.iter() {
+            if !units.contains(&node.unit.as_str()) {
+                units.push(&node.unit);
+            }
+        }
+        units
+    }
+
+    /// Parses a `systemctl list-dependencies` output,
+    /// either drawn as a tree or `--plain`
+    pub fn parse_tree(content: &str) -> std::io::Result<DependencyNode> {
+        let mut lines = content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(parse_tree_line);
+        let (_, root) = lines.next().ok_or_else(|| {
+            std::io::Error::new(std::io::ErrorKind::InvalidData, "empty dependency tree")
+        })?;
+        // stack of the nodes being built: one per depth
+        let mut stack = vec![DependencyNode {
+            unit: root,
+            children: Vec::new(),
+        }];
+        for (depth, unit) in lines {
+            let depth = depth.max(1);
+            while stack.len() > depth {
+                let node = stack.pop().unwrap();
+                stack.last_mut().unwrap().children.push(node);
+            }
+            stack.push(DependencyNode {
+                unit,
+                children: Vec::new(),
+            });
+        }
+        while stack.len() > 1 {
+            let node = stack.pop().unwrap();
+            stack.last_mut().unwrap().children.push(node);
+        }
+        Ok(stack.pop().unwrap())
+    }
+}
+
+/// Depth first iterator over transitive dependencies, see [DependencyNode::iter]
+#[derive(Clone, Debug)]
+pub struct DependencyIter<'a> {
+    stack: Vec<&'a DependencyNode>,
+}
+
+impl<'a> Iterator for DependencyIter<'a> {
+    type Item = &'a DependencyNode;
+    fn next(&mut self) -> Option<Self::Item> {
+        let node = self.stack.pop()?;
+        self.stack.extend(node.children.iter().rev());
+        Some(node)
+    }
+}
+
+/// Splits a `list-dependencies` line into its depth and unit name.
+/// Each level is drawn with two characters, in UTF-8 or ASCII.
+fn parse_tree_line(line: &str) -> (usize, String) {
+    let mut chars: &[char] = &line.chars().collect::<Vec<_>>();
+    // leading state bullet
+    if let [bullet, ' ', rest @ ..] = chars {
+        if "●○×*↻".contains(*bullet) {
+            chars = rest;
+        }
+    }
+    let mut depth = 0;
+    while let [a, b, rest @ ..] = chars {
+        match (a, b) {
+            ('│', ' ')
+            | (' ', ' ')
+            | ('├', '─')
+            | ('└', '─')
+            | ('|', ' ')
+            | ('|', '-')
+            | ('`', '-') => {
+                depth += 1;
+                chars = rest;
+            },
+            _ => break,
+        }
+    }
+    let unit: String = chars.iter().collect();
+    (depth, unit.trim().to_string())
+}
+
+impl SystemCtl {
+    /// Returns the dependency tree of given `unit` (`systemctl list-dependencies`).
+    ///  + reverse: lists the units depending on `unit` instead (`--reverse`)
+    ///  + kind: requirement or ordering dependencies
+    ///  + all: expands all units recursively, not only targets (`--all`)
+    pub fn list_dependencies(
+        &self,
+        unit: &str,
+        reverse: bool,
+        kind: DependencyKind,
+        all: bool,
+    ) -> std::io::Result<DependencyNode> {
+        let mut args = vec!["list-dependencies", "--full", "--no-pager"];
+        if reverse {
+            args.push("--reverse");
+        }
+        match kind {
+            DependencyKind::Requirement => {},
+            DependencyKind::After => args.push("--after"),
+            DependencyKind::Before => args.push("--before"),
+        }
+        if all {
+            args.push("--all");
+        }
+        args.push(unit);
+        let content = self.systemctl_capture(args)?;
+        DependencyNode::parse_tree(&content.stdout)
+    }
+
+    /// Returns the units that given `unit` is ordered after,
+    /// meaning they are started before `unit` (`list-dependencies --after`).
+    /// Ordering does not imply requirement: listed units are not necessarily pulled in.
+    pub fn list_ordered_after(&self, unit: &str) -> std::io::Result<Vec<String>> {
+        let tree = self.list_dependencies(unit, false, DependencyKind::After, false)?;
+        Ok(tree.units().into_iter().map(str::to_string).collect())
+    }
+
+    /// Returns the units that given `unit` is ordered before,
+    /// meaning they are started after `unit` (`list-dependencies --before`).
+    /// Ordering does not imply requirement: listed units are not necessarily pulled in.
+    pub fn list_ordered_before(&self, unit: &str) -> std::io::Result<Vec<String>> {
+        let tree = self.list_dependencies(unit, false, DependencyKind::Before, false)?;
+        Ok(tree.units().into_iter().map(str::to_string).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn leaf(unit: &str) -> DependencyNode {
+        DependencyNode {
+            unit: unit.to_string(),
+            children: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn test_parse_dependency_tree() {
+        let expected = DependencyNode {
+            unit: "cron.service".into(),
+            children: vec![
+                leaf("system.slice"),
+                DependencyNode {
+                    unit: "sysinit.target".into(),
+                    children: vec![
+                        DependencyNode {
+                            unit: "-.mount".into(),
+                            children: vec![leaf("system.slice")],
+                        },
+                        leaf("dev-hugepages.mount"),
+                    ],
+                },
+                leaf("basic.target"),
+            ],
+        };
+        let tree = "\
+cron.service
+● ├─system.slice
+○ ├─sysinit.target
+● │ ├─-.mount
+● │ │ └─system.slice
+○ │ └─dev-hugepages.mount
+● └─basic.target";
+        assert_eq!(DependencyNode::parse_tree(tree).unwrap(), expected);
+
+        let ascii = "\
+cron.service
+* |-system.slice
+* |-sysinit.target
+* | |--.mount
+* | | `-system.slice
+* | `-dev-hugepages.mount
+* `-basic.target";
+        assert_eq!(DependencyNode::parse_tree(ascii).unwrap(), expected);
+
+        let plain = "\
+cron.service
+  system.slice
+  sysinit.target
+    -.mount
+      system.slice
+    dev-hugepages.mount
+  basic.target";
+        let tree = DependencyNode::parse_tree(plain).unwrap();
+        assert_eq!(tree, expected);
+
+        let units: Vec<&str> = tree.iter().map(|node| node.unit.as_str()).collect();
+        assert_eq!(
+            units,
+            vec![
+                "system.slice",
+                "sysinit.target",
+                "-.mount",
+                "system.slice",
+                "dev-hugepages.mount",
+                "basic.target"
+            ]
+        );
+        assert_eq!(
+            tree.units(),
+            vec![
+                "system.slice",
+                "sysinit.target",
+                "-.mount",
+                "dev-hugepages.mount",
+                "basic.target"
+            ]
+        );
+        assert!(DependencyNode::parse_tree("").is_err());
+    }
+}
@@ -0,0 +1,252 @@
+//! Offline unit file discovery, scanning the unit directories
+//! directly instead of asking the service manager
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Component, Path, PathBuf};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// System unit directories, by decreasing priority
+pub const SYSTEM_UNIT_DIRS: &[&str] = &[
+    "/etc/systemd/system",
+    "/run/systemd/system",
+    "/usr/local/lib/systemd/system",
+    "/usr/lib/systemd/system",
+    "/lib/systemd/system",
+];
+
+/// Suffixes of unit files
+const UNIT_SUFFIXES: &[&str] = &[
+    "service",
+    "socket",
+    "device",
+    "mount",
+    "automount",
+    "swap",
+    "target",
+    "path",
+    "timer",
+    "slice",
+    "scope",
+];
+
+/// A unit file found on disk by [discover_units]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct DiscoveredUnit {
+    /// Unit name
+    pub name: String,
+    /// Effective unit file, from the directory of highest priority
+    pub path: PathBuf,
+    /// Files of the same name in directories of lower priority,
+    /// shadowed by `path`, by decreasing priority
+    pub shadowed: Vec<PathBuf>,
+    /// Effective drop-ins (`$name.d/*.conf`), in application order.
+    /// A drop-in shadows the drop-ins of the same name in
+    /// directories of lower priority.
+    pub drop_ins: Vec<PathBuf>,
+    /// `true` if `path` is a symlink to `/dev/null`
+    pub masked: bool,
+    /// Unit this name is an alias of, if `path` is a symlink to another unit
+    pub alias_of: Option<String>,
+}
+
+/// Lists the unit files of the system unit directories ([SYSTEM_UNIT_DIRS])
+/// below `root`, `/` for the running system or the path of a chroot or image.
+/// Units are sorted by name.
+pub fn discover_units(root: impl AsRef<Path>) -> std::io::Result<Vec<DiscoveredUnit>> {
+    discover_units_in(root, SYSTEM_UNIT_DIRS)
+}
+
+/// Lists the unit files of given directories below `root`.
+/// `dirs` are absolute paths, by decreasing priority.
+/// Missing directories are skipped.
+pub fn discover_units_in(
+    root: impl AsRef<Path>,
+    dirs: &[&str],
+) -> std::io::Result<Vec<DiscoveredUnit>> {
+    let root = root.as_ref();
+    let mut scanned: Vec<PathBuf> = Vec::new();
+    let mut units: BTreeMap<String, DiscoveredUnit> = BTreeMap::new();
+    // drop-in file name -> path, per unit
+    let mut drop_ins: BTreeMap<String, BTreeMap<String, PathBuf>> = BTreeMap::new();
+    for dir in dirs {
+        // `/lib` is usually a symlink to `/usr/lib`
+        let Some(resolved) = resolve_below(root, Path::new(dir)) else {
+            continue;
+        };
+        if scanned.contains(&resolved) {
+            continue;
+        }
+        let dir = root.join(&resolved);
+        scanned.push(resolved);
+        if !dir.is_dir() {
+            continue;
+        }
+        for entry in std::fs::read_dir(&dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let path = entry.path();
+            if let Some(unit) = name.strip_suffix(".d") {
+                if is_unit_name(unit) && path.is_dir() {
+                    let found = drop_ins.entry(unit.to_string()).or_default();
+                    for conf in std::fs::read_dir(&path)? {
+                        let conf = conf?;
+                        let conf_name = conf.file_name().to_string_lossy().to_string();
+                        if conf_name.ends_with(".conf") {
+                            found.entry(conf_name).or_insert_with(|| conf.path());
+                        }
+                    }
+                }
+                continue;
+            }
+            if !is_unit_name(&name) || path.is_dir() {
+                continue;
+            }
+            if let Some(unit) = units.get_mut(&name) {
+                unit.shadowed.push(path);
+                continue;
+            }
+            let target = std::fs::read_link(&path).ok();
+            let masked = target.as_deref() == Some(Path::new("/dev/null"));
+            let alias_of = target
+                .as_deref()
+                .and_then(Path::file_name)
+                .map(|target| target.to_string_lossy().to_string())
+                .filter(|target| *target != name && is_unit_name(target));
+            units.insert(
+                name.clone(),
+                DiscoveredUnit {
+                    name,
+                    path,
+                    masked,
+                    alias_of,
+                    ..Default::default()
+                },
+            );
+        }
+    }
+    for (name, found) in drop_ins {
+        if let Some(unit) = units.get_mut(&name) {
+            unit.drop_ins = found.into_values().collect();
+        }
+    }
+    Ok(units.into_values().collect())
+}
+
+/// Resolves the symlinks of `path` as if `root` was the file system root,
+/// like a chroot would: absolute link targets are looked up below `root`.
+/// Returns the resolved path, relative to `root`, or `None` if it does not exist.
+fn resolve_below(root: &Path, path: &Path) -> Option<PathBuf> {
+    // components left to resolve, last one first
+    fn pending_of(path: &Path) -> impl Iterator<Item = Component<'_>> {
+        path.components()
+            .rev()
+            .filter(|c| matches!(c, Component::Normal(_) | Component::ParentDir))
+    }
+    let mut resolved = PathBuf::new();
+    let mut pending: Vec<OsString> = pending_of(path).map(|c| c.as_os_str().into()).collect();
+    let mut links = 0;
+    while let Some(name) = pending.pop() {
+        if name == ".." {
+            resolved.pop();
+            continue;
+        }
+        let candidate = resolved.join(&name);
+        let metadata = std::fs::symlink_metadata(root.join(&candidate)).ok()?;
+        if !metadata.file_type().is_symlink() {
+            resolved = candidate;
+            continue;
+        }
+        // bounded, like the kernel does, in case of loops
+        links += 1;
+        if links > 40 {
+            return None;
+        }
+        let target = std::fs::read_link(root.join(&candidate)).ok()?;
+        if target.is_absolute() {
+            resolved = PathBuf::new();
+        }
+        pending.extend(pending_of(&target).map(|c| c.as_os_str().into()));
+    }
+    Some(resolved)
+}
+
+fn is_unit_name(name: &str) -> bool {
+    match name.rsplit_once('.') {
+        Some((prefix, suffix)) => !prefix.is_empty() && UNIT_SUFFIXES.contains(&suffix),
+        None => false,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::fs;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn test_discover_units() {
+        let root = std::env::temp_dir().join(format!("systemctl-discovery-{}", std::process::id()));
+        let etc = root.join("etc/systemd/system");
+        let run = root.join("run/systemd/system");
+        let lib = root.join("usr/lib/systemd/system");
+        for dir in [
+            &etc,
+            &run,
+            &lib,
+            &etc.join("foo.service.d"),
+            &lib.join("foo.service.d"),
+        ] {
+            fs::create_dir_all(dir).unwrap();
+        }
+        symlink("usr/lib", root.join("lib")).unwrap();
+        // absolute links point inside the image, not to the host
+        let opt = root.join("opt/units");
+        fs::create_dir_all(&opt).unwrap();
+        fs::create_dir_all(root.join("usr/local/lib/systemd")).unwrap();
+        symlink("/opt/units", root.join("usr/local/lib/systemd/system")).unwrap();
+        fs::write(opt.join("qux.service"), "[Service]\n").unwrap();
+        fs::write(lib.join("foo.service"), "[Service]\n").unwrap();
+        fs::write(etc.join("foo.service"), "[Service]\n").unwrap();
+        fs::write(lib.join("foo.service.d/10-limits.conf"), "").unwrap();
+        fs::write(lib.join("foo.service.d/20-env.conf"), "").unwrap();
+        fs::write(etc.join("foo.service.d/10-limits.conf"), "").unwrap();
+        fs::write(lib.join("bar.service"), "[Service]\n").unwrap();
+        symlink("/dev/null", run.join("bar.service")).unwrap();
+        symlink(lib.join("foo.service"), etc.join("baz.service")).unwrap();
+        fs::create_dir(etc.join("multi-user.target.wants")).unwrap();
+        fs::write(lib.join("README"), "").unwrap();
+
+        let units = discover_units(&root).unwrap();
+        let names: Vec<&str> = units.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(
+            names,
+            vec!["bar.service", "baz.service", "foo.service", "qux.service"]
+        );
+        assert_eq!(units[3].path, opt.join("qux.service"));
+
+        let bar = &units[0];
+        assert!(bar.masked);
+        assert_eq!(bar.path, run.join("bar.service"));
+        assert_eq!(bar.shadowed, vec![lib.join("bar.service")]);
+
+        let baz = &units[1];
+        assert_eq!(baz.alias_of.as_deref(), Some("foo.service"));
+        assert!(!baz.masked);
+
+        let foo = &units[2];
+        assert_eq!(foo.path, etc.join("foo.service"));
+        // `/lib` is not scanned twice
+        assert_eq!(foo.shadowed, vec![lib.join("foo.service")]);
+        assert_eq!(
+            foo.drop_ins,
+            vec![
+                etc.join("foo.service.d/10-limits.conf"),
+                lib.join("foo.service.d/20-env.conf")
+            ]
+        );
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
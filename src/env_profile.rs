@@ -0,0 +1,126 @@
+//! Environment of the spawned commands
+use bon::Builder;
+use std::collections::BTreeMap;
+use std::process::Command;
+
+/// Environment handed to every command spawned by [SystemCtl](crate::SystemCtl),
+/// see [SystemCtlBuilder::env_profile](crate::SystemCtlBuilder::env_profile).
+///
+/// Variables are applied in this order: host environment is cleared
+/// (if requested) except for allowed variables, then `remove`d
+/// variables are unset and finally `vars` are set.
+///
+/// ```
+/// use std::collections::BTreeMap;
+/// use systemctl::{EnvProfile, SystemCtl};
+///
+/// let profile = EnvProfile::builder()
+///     .clear(true)
+///     .allow(vec!["PATH".to_string()])
+///     .vars(BTreeMap::from([(
+///         "SYSTEMD_LOG_LEVEL".to_string(),
+///         "debug".to_string(),
+///     )]))
+///     .build();
+/// let ctl = SystemCtl::builder()
+///     .additional_args(vec![])
+///     .env_profile(profile)
+///     .build();
+/// ```
+#[derive(Builder, Default, Clone, Debug, PartialEq, Eq)]
+pub struct EnvProfile {
+    /// Starts from an empty environment instead of the host one
+    #[builder(default)]
+    pub clear: bool,
+    /// Host variables kept when `clear` is set
+    #[builder(default)]
+    pub allow: Vec<String>,
+    /// Host variables removed from the environment
+    #[builder(default)]
+    pub remove: Vec<String>,
+    /// Variables set on top of the environment
+    #[builder(default)]
+    pub vars: BTreeMap<String, String>,
+}
+
+impl EnvProfile {
+    /// Host independent environment: only the variables needed to reach
+    /// the service manager are kept, output is neither localized,
+    /// colored nor paged
+    pub fn minimal() -> Self {
+        let allow = [
+            "PATH",
+            "HOME",
+            "USER",
+            "XDG_RUNTIME_DIR",
+            "DBUS_SESSION_BUS_ADDRESS",
+            "DBUS_SYSTEM_BUS_ADDRESS",
+        ];
+        let vars = [
+            ("LC_ALL", "C"),
+            ("SYSTEMD_COLORS", "0"),
+            ("SYSTEMD_PAGER", ""),
+            ("SYSTEMD_URLIFY", "0"),
+        ];
+        Self {
+            clear: true,
+            allow: allow.iter().map(|var| var.to_string()).collect(),
+            remove: Vec::new(),
+            vars: vars
+                .iter()
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+        }
+    }
+
+    /// Applies this profile to given command
+    pub fn apply(&self, command: &mut Command) {
+        if self.clear {
+            command.env_clear();
+            for key in &self.allow {
+                if let Some(value) = std::env::var_os(key) {
+                    command.env(key, value);
+                }
+            }
+        }
+        for key in &self.remove {
+            command.env_remove(key);
+        }
+        command.envs(&self.vars);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::SystemCtl;
+
+    #[test]
+    fn test_env_profile() {
+        let profile = EnvProfile::builder()
+            .clear(true)
+            .allow(vec!["PATH".to_string(), "SYSTEMCTL_UNSET_VAR".to_string()])
+            .vars(BTreeMap::from([(
+                "SYSTEMD_LOG_LEVEL".to_string(),
+                "debug".to_string(),
+            )]))
+            .build();
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .path("/usr/bin/env".to_string())
+            .env_profile(profile)
+            .build();
+        let output = ctl.systemctl_capture(vec![]).unwrap();
+        let mut vars: Vec<&str> = output.stdout.lines().collect();
+        vars.sort();
+        let expected_path = format!("PATH={}", std::env::var("PATH").unwrap());
+        assert_eq!(
+            vars,
+            vec![expected_path.as_str(), "SYSTEMD_LOG_LEVEL=debug"]
+        );
+
+        let minimal = EnvProfile::minimal();
+        assert!(minimal.clear);
+        assert_eq!(minimal.vars["LC_ALL"], "C");
+    }
+}
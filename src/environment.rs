@@ -0,0 +1,159 @@
+//! Environment block of the service manager, inherited by the units
+use crate::{RunResult, SystemCtl};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+
+impl SystemCtl {
+    /// Returns the environment passed by the manager to the units
+    /// (`systemctl show-environment`)
+    pub fn show_environment(&self) -> std::io::Result<HashMap<String, String>> {
+        let content = self.systemctl_capture(["show-environment"])?;
+        Ok(parse_environment(&content.stdout))
+    }
+
+    /// Sets given variables in the manager environment
+    /// (`systemctl set-environment`)
+    ///
+    /// ```no_run
+    /// let ctl = systemctl::SystemCtl::builder()
+    ///     .additional_args(vec!["--user".to_string()])
+    ///     .build();
+    /// ctl.set_environment(&[("DISPLAY", ":0")]).unwrap();
+    /// ```
+    pub fn set_environment(&self, vars: &[(&str, &str)]) -> std::io::Result<RunResult> {
+        let names: Vec<&str> = vars.iter().map(|(name, _)| *name).collect();
+        check_names(&names)?;
+        let assignments: Vec<String> = vars
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, value))
+            .collect();
+        let mut args = vec!["set-environment"];
+        args.extend(assignments.iter().map(String::as_str));
+        self.systemctl_capture(args)
+    }
+
+    /// Removes given variables from the manager environment
+    /// (`systemctl unset-environment`)
+    pub fn unset_environment(&self, names: &[&str]) -> std::io::Result<RunResult> {
+        check_names(names)?;
+        let mut args = vec!["unset-environment"];
+        args.extend_from_slice(names);
+        self.systemctl_capture(args)
+    }
+
+    /// Copies given variables of the calling process environment into
+    /// the manager environment (`systemctl import-environment`).
+    /// Variables missing from the calling process are ignored.
+    pub fn import_environment(&self, names: &[&str]) -> std::io::Result<RunResult> {
+        check_names(names)?;
+        let mut args = vec!["import-environment"];
+        args.extend_from_slice(names);
+        self.systemctl_capture(args)
+    }
+}
+
+/// Rejects empty lists and invalid variable names
+fn check_names(names: &[&str]) -> std::io::Result<()> {
+    if names.is_empty() {
+        return Err(Error::new(ErrorKind::InvalidInput, "no variable given"));
+    }
+    for name in names {
+        if name.is_empty() || name.contains(['=', '\0']) {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid variable name \"{}\"", name),
+            ));
+        }
+    }
+    Ok(())
+}
+
+/// Parses the `KEY=VALUE` lines of `systemctl show-environment`.
+/// Values holding special characters are printed `$'..'` quoted.
+fn parse_environment(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(key, value)| {
+            let value = match value.strip_prefix("$'").and_then(|v| v.strip_suffix('\'')) {
+                Some(quoted) => unquote(quoted),
+                None => value.to_string(),
+            };
+            (key.to_string(), value)
+        })
+        .collect()
+}
+
+/// Reverts the C-style escapes of `$'..'` quoting
+fn unquote(quoted: &str) -> String {
+    let mut bytes = Vec::with_capacity(quoted.len());
+    let mut chars = quoted.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            let mut buffer = [0; 4];
+            bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            continue;
+        }
+        match chars.next() {
+            Some('n') => bytes.push(b'\n'),
+            Some('t') => bytes.push(b'\t'),
+            Some('r') => bytes.push(b'\r'),
+            Some('a') => bytes.push(0x07),
+            Some('b') => bytes.push(0x08),
+            Some('f') => bytes.push(0x0c),
+            Some('v') => bytes.push(0x0b),
+            Some('x') => {
+                let hex: String = chars.by_ref().take(2).collect();
+                match u8::from_str_radix(&hex, 16) {
+                    Ok(byte) => bytes.push(byte),
+                    Err(_) => bytes.extend_from_slice(format!("\\x{}", hex).as_bytes()),
+                }
+            },
+            Some(c) => {
+                let mut buffer = [0; 4];
+                bytes.extend_from_slice(c.encode_utf8(&mut buffer).as_bytes());
+            },
+            None => bytes.push(b'\\'),
+        }
+    }
+    String::from_utf8_lossy(&bytes).into_owned()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_parse_environment() {
+        let content = "\
+LANG=en_US.UTF-8
+PATH=/usr/local/bin:/usr/bin
+DISPLAY=:0
+MOTD=$'hello\\n\\'world\\' \\x21'
+";
+        let env = parse_environment(content);
+        assert_eq!(env.len(), 4);
+        assert_eq!(env["PATH"], "/usr/local/bin:/usr/bin");
+        assert_eq!(env["DISPLAY"], ":0");
+        assert_eq!(env["MOTD"], "hello\n'world' !");
+    }
+
+    #[test]
+    fn test_set_environment() {
+        let mock = MockExecutor::new();
+        mock.on(&[], MockResponse::success(""));
+        let ctl = mock.ctl();
+        ctl.set_environment(&[("DISPLAY", ":0"), ("GREETING", "hello world")])
+            .unwrap();
+        ctl.import_environment(&["SSH_AUTH_SOCK"]).unwrap();
+        let calls = mock.calls();
+        assert_eq!(
+            calls[0].args,
+            ["set-environment", "DISPLAY=:0", "GREETING=hello world"]
+        );
+        assert_eq!(calls[1].args, ["import-environment", "SSH_AUTH_SOCK"]);
+        assert!(ctl.unset_environment(&[]).is_err());
+        assert!(ctl.set_environment(&[("A=B", "c")]).is_err());
+    }
+}
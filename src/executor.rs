@@ -0,0 +1,49 @@
+//! Process execution layer, replaceable to run commands elsewhere or to fake them
+use crate::{timeout, SystemCtl};
+use std::process::{Command, Output, Stdio};
+use std::time::Duration;
+
+/// Runs the commands of a [SystemCtl]: `systemctl` and the other systemd
+/// tools (`systemd-analyze`, `machinectl`..), see
+/// [SystemCtlBuilder::executor](crate::SystemCtlBuilder::executor).
+/// Commands are fully prepared: arguments, environment and
+/// [CommandHook](crate::CommandHook) are already applied.
+///
+/// The `test-util` feature provides a `MockExecutor`, answering
+/// scripted responses without spawning anything.
+pub trait SystemctlExecutor: std::fmt::Debug + Send + Sync {
+    /// Runs `command` to completion and returns its exit status and outputs.
+    /// Commands lasting longer than `timeout`, if any, are expected to fail
+    /// with a [std::io::ErrorKind::TimedOut] error, like [CommandTimedOut](crate::CommandTimedOut).
+    fn execute(&self, command: &mut Command, timeout: Option<Duration>) -> std::io::Result<Output>;
+}
+
+/// Default [SystemctlExecutor], spawning local processes. Both outputs
+/// are read while the process runs, it is killed on timeout.
+#[derive(Copy, Clone, Debug, Default)]
+pub struct ProcessExecutor;
+
+impl SystemctlExecutor for ProcessExecutor {
+    fn execute(&self, command: &mut Command, timeout: Option<Duration>) -> std::io::Result<Output> {
+        let child = command
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        timeout::capture(child, timeout)
+    }
+}
+
+impl SystemCtl {
+    /// Runs given command to completion with the configured executor
+    pub(crate) fn execute(
+        &self,
+        mut command: Command,
+        timeout: Option<Duration>,
+    ) -> std::io::Result<Output> {
+        self.check_target()?;
+        match &self.executor {
+            Some(executor) => executor.execute(&mut command, timeout),
+            None => ProcessExecutor.execute(&mut command, timeout),
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! Exit status names, as known by systemd (`systemd-analyze exit-status`)
+use strum_macros::EnumString;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Origin of an exit status definition
+#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ExitStatusClass {
+    /// C library definitions (`EXIT_SUCCESS`, `EXIT_FAILURE`)
+    #[strum(serialize = "libc")]
+    Libc,
+    /// LSB init scripts definitions
+    #[strum(serialize = "LSB")]
+    Lsb,
+    /// BSD `sysexits.h` definitions
+    #[strum(serialize = "BSD")]
+    Bsd,
+    /// systemd specific definitions, reported when the service
+    /// could not be set up before executing the actual binary
+    #[strum(serialize = "systemd")]
+    Systemd,
+}
+
+/// Built-in exit status table, kept in sync with systemd's `exit-status.c`
+const EXIT_STATUSES: &[(u8, &str, ExitStatusClass)] = &[
+    (0, "SUCCESS", ExitStatusClass::Libc),
+    (1, "FAILURE", ExitStatusClass::Libc),
+    (2, "INVALIDARGUMENT", ExitStatusClass::Lsb),
+    (3, "NOTIMPLEMENTED", ExitStatusClass::Lsb),
+    (4, "NOPERMISSION", ExitStatusClass::Lsb),
+    (5, "NOTINSTALLED", ExitStatusClass::Lsb),
+    (6, "NOTCONFIGURED", ExitStatusClass::Lsb),
+    (7, "NOTRUNNING", ExitStatusClass::Lsb),
+    (64, "USAGE", ExitStatusClass::Bsd),
+    (65, "DATAERR", ExitStatusClass::Bsd),
+    (66, "NOINPUT", ExitStatusClass::Bsd),
+    (67, "NOUSER", ExitStatusClass::Bsd),
+    (68, "NOHOST", ExitStatusClass::Bsd),
+    (69, "UNAVAILABLE", ExitStatusClass::Bsd),
+    (70, "SOFTWARE", ExitStatusClass::Bsd),
+    (71, "OSERR", ExitStatusClass::Bsd),
+    (72, "OSFILE", ExitStatusClass::Bsd),
+    (73, "CANTCREAT", ExitStatusClass::Bsd),
+    (74, "IOERR", ExitStatusClass::Bsd),
+    (75, "TEMPFAIL", ExitStatusClass::Bsd),
+    (76, "PROTOCOL", ExitStatusClass::Bsd),
+    (77, "NOPERM", ExitStatusClass::Bsd),
+    (78, "CONFIG", ExitStatusClass::Bsd),
+    (200, "CHDIR", ExitStatusClass::Systemd),
+    (201, "NICE", ExitStatusClass::Systemd),
+    (202, "FDS", ExitStatusClass::Systemd),
+    (203, "EXEC", ExitStatusClass::Systemd),
+    (204, "MEMORY", ExitStatusClass::Systemd),
+    (205, "LIMITS", ExitStatusClass::Systemd),
+    (206, "OOM_ADJUST", ExitStatusClass::Systemd),
+    (207, "SIGNAL_MASK", ExitStatusClass::Systemd),
+    (208, "STDIN", ExitStatusClass::Systemd),
+    (209, "STDOUT", ExitStatusClass::Systemd),
+    (210, "CHROOT", ExitStatusClass::Systemd),
+    (211, "IOPRIO", ExitStatusClass::Systemd),
+    (212, "TIMERSLACK", ExitStatusClass::Systemd),
+    (213, "SECUREBITS", ExitStatusClass::Systemd),
+    (214, "SETSCHEDULER", ExitStatusClass::Systemd),
+    (215, "CPUAFFINITY", ExitStatusClass::Systemd),
+    (216, "GROUP", ExitStatusClass::Systemd),
+    (217, "USER", ExitStatusClass::Systemd),
+    (218, "CAPABILITIES", ExitStatusClass::Systemd),
+    (219, "CGROUP", ExitStatusClass::Systemd),
+    (220, "SETSID", ExitStatusClass::Systemd),
+    (221, "CONFIRM", ExitStatusClass::Systemd),
+    (222, "STDERR", ExitStatusClass::Systemd),
+    (224, "PAM", ExitStatusClass::Systemd),
+    (225, "NETWORK", ExitStatusClass::Systemd),
+    (226, "NAMESPACE", ExitStatusClass::Systemd),
+    (227, "NO_NEW_PRIVILEGES", ExitStatusClass::Systemd),
+    (228, "SECCOMP", ExitStatusClass::Systemd),
+    (229, "SELINUX_CONTEXT", ExitStatusClass::Systemd),
+    (230, "PERSONALITY", ExitStatusClass::Systemd),
+    (231, "APPARMOR_PROFILE", ExitStatusClass::Systemd),
+    (232, "ADDRESS_FAMILIES", ExitStatusClass::Systemd),
+    (233, "RUNTIME_DIRECTORY", ExitStatusClass::Systemd),
+    (235, "CHOWN", ExitStatusClass::Systemd),
+    (236, "SMACK_PROCESS_LABEL", ExitStatusClass::Systemd),
+    (237, "KEYRING", ExitStatusClass::Systemd),
+    (238, "STATE_DIRECTORY", ExitStatusClass::Systemd),
+    (239, "CACHE_DIRECTORY", ExitStatusClass::Systemd),
+    (240, "LOGS_DIRECTORY", ExitStatusClass::Systemd),
+    (241, "CONFIGURATION_DIRECTORY", ExitStatusClass::Systemd),
+    (242, "NUMA_POLICY", ExitStatusClass::Systemd),
+    (243, "CREDENTIALS", ExitStatusClass::Systemd),
+    (245, "BPF", ExitStatusClass::Systemd),
+    (255, "EXCEPTION", ExitStatusClass::Systemd),
+];
+
+/// Returns the systemd name of given exit status, like `EXEC` for `203`,
+/// for example to translate the `ExecMainStatus` property of a unit.
+/// Note that values 2 to 7 only follow the LSB convention
+/// for init scripts.
+pub fn exit_status_name(code: i32) -> Option<&'static str> {
+    exit_status_entry(code).map(|(_, name, _)| name)
+}
+
+/// Returns the class of given exit status, if known
+pub fn exit_status_class(code: i32) -> Option<ExitStatusClass> {
+    exit_status_entry(code).map(|(_, _, class)| class)
+}
+
+/// Returns the exit status known by given name, like `203` for `EXEC`.
+/// Lookup is case insensitive.
+pub fn exit_status_code(name: &str) -> Option<i32> {
+    EXIT_STATUSES
+        .iter()
+        .find(|(_, n, _)| n.eq_ignore_ascii_case(name))
+        .map(|(code, _, _)| i32::from(*code))
+}
+
+fn exit_status_entry(code: i32) -> Option<(u8, &'static str, ExitStatusClass)> {
+    let code = u8::try_from(code).ok()?;
+    EXIT_STATUSES.iter().find(|(c, _, _)| *c == code).copied()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_exit_status_lookup() {
+        assert_eq!(exit_status_name(0), Some("SUCCESS"));
+        assert_eq!(exit_status_name(203), Some("EXEC"));
+        assert_eq!(exit_status_name(226), Some("NAMESPACE"));
+        assert_eq!(exit_status_name(234), None);
+        assert_eq!(exit_status_name(-1), None);
+        assert_eq!(exit_status_class(78), Some(ExitStatusClass::Bsd));
+        assert_eq!(exit_status_class(203), Some(ExitStatusClass::Systemd));
+        assert_eq!(exit_status_code("exec"), Some(203));
+        assert_eq!(exit_status_code("unknown"), None);
+        for (code, name, _) in EXIT_STATUSES {
+            assert_eq!(exit_status_code(name), Some(i32::from(*code)));
+        }
+    }
+}
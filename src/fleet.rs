@@ -0,0 +1,212 @@
+//! Aggregate operations over several systemd instances
+use crate::{RunResult, SystemCtl, Unit, UnitList};
+use std::thread;
+
+/// A `Fleet` groups several [SystemCtl] handles, usually each one
+/// targeting a different host (`--host`) or container (`--machine`),
+/// and runs operations on all of them at once.
+///
+/// Operations are executed concurrently, one thread per member,
+/// and never stop on the first error: the outcome of each member
+/// is reported in the returned [FleetResult].
+#[derive(Clone, Debug, Default)]
+pub struct Fleet {
+    members: Vec<(String, SystemCtl)>,
+}
+
+impl Fleet {
+    /// Creates an empty `Fleet`
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Adds a member identified by `name`, for example the targeted host name
+    pub fn with_member(mut self, name: impl Into<String>, ctl: SystemCtl) -> Self {
+        self.add_member(name, ctl);
+        self
+    }
+
+    /// Adds a member identified by `name`, for example the targeted host name
+    pub fn add_member(&mut self, name: impl Into<String>, ctl: SystemCtl) {
+        self.members.push((name.into(), ctl));
+    }
+
+    /// Iterates over the members of this `Fleet`
+    pub fn members(&self) -> impl Iterator<Item = (&str, &SystemCtl)> {
+        self.members.iter().map(|(name, ctl)| (name.as_str(), ctl))
+    }
+
+    /// Number of members
+    pub fn len(&self) -> usize {
+        self.members.len()
+    }
+
+    /// Returns `true` if this `Fleet` has no member
+    pub fn is_empty(&self) -> bool {
+        self.members.is_empty()
+    }
+
+    /// Runs `op` against every member concurrently
+    pub fn run<T, F>(&self, op: F) -> FleetResult<T>
+    where
+        T: Send,
+        F: Fn(&SystemCtl) -> std::io::Result<T> + Sync,
+    {
+        let op = &op;
+        let results = thread::scope(|scope| {
+            let handles: Vec<_> = self
+                .members
+                .iter()
+                .map(|(name, ctl)| (name, scope.spawn(move || op(ctl))))
+                .collect();
+            handles
+                .into_iter()
+                .map(|(name, handle)| {
+                    let result = handle
+                        .join()
+                        .unwrap_or_else(|_| Err(std::io::Error::other("fleet operation panicked")));
+                    (name.clone(), result)
+                })
+                .collect()
+        });
+        FleetResult { results }
+    }
+
+    /// Returns `true` for every member where given `unit` is actively running
+    pub fn is_active(&self, unit: &str) -> FleetResult<bool> {
+        self.run(|ctl| ctl.is_active(unit))
+    }
+
+    /// Returns raw `systemctl status $unit` of every member
+    pub fn status(&self, unit: &str) -> FleetResult<RunResult> {
+        self.run(|ctl| ctl.status(unit))
+    }
+
+    /// Forces given `unit` to (re)start on every member
+    pub fn restart(&self, unit: &str) -> FleetResult<RunResult> {
+        self.run(|ctl| ctl.restart(unit))
+    }
+
+    /// Collects a snapshot of given `unit` on every member,
+    /// see [SystemCtl::create_unit]
+    pub fn create_unit(&self, unit: &str) -> FleetResult<Unit> {
+        self.run(|ctl| ctl.create_unit(unit))
+    }
+
+    /// Collects the unit listing of every member,
+    /// see [SystemCtl::list_units_full]
+    pub fn list_units_full(
+        &self,
+        type_filter: Option<&str>,
+        state_filter: Option<&str>,
+        glob: Option<&str>,
+    ) -> FleetResult<Vec<UnitList>> {
+        self.run(|ctl| ctl.list_units_full(type_filter, state_filter, glob))
+    }
+}
+
+/// Per member outcome of a [Fleet] operation, in insertion order
+#[derive(Debug)]
+pub struct FleetResult<T> {
+    results: Vec<(String, std::io::Result<T>)>,
+}
+
+impl<T> FleetResult<T> {
+    /// Iterates over each member name and its outcome
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &std::io::Result<T>)> {
+        self.results
+            .iter()
+            .map(|(name, result)| (name.as_str(), result))
+    }
+
+    /// Returns the outcome for member `name`
+    pub fn get(&self, name: &str) -> Option<&std::io::Result<T>> {
+        self.iter()
+            .find(|(member, _)| *member == name)
+            .map(|(_, result)| result)
+    }
+
+    /// Returns `true` if the operation succeeded on every member
+    pub fn is_ok(&self) -> bool {
+        self.results.iter().all(|(_, result)| result.is_ok())
+    }
+
+    /// Iterates over successful members and their value
+    pub fn successes(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.iter()
+            .filter_map(|(name, result)| result.as_ref().ok().map(|value| (name, value)))
+    }
+
+    /// Iterates over failed members and their error
+    pub fn errors(&self) -> impl Iterator<Item = (&str, &std::io::Error)> {
+        self.iter()
+            .filter_map(|(name, result)| result.as_ref().err().map(|e| (name, e)))
+    }
+
+    /// Converts into a list of member names and outcomes
+    pub fn into_inner(self) -> Vec<(String, std::io::Result<T>)> {
+        self.results
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+    use std::sync::Barrier;
+
+    #[test]
+    fn test_fleet_result_reporting() {
+        let fleet = Fleet::new()
+            .with_member("alpha", SystemCtl::default())
+            .with_member("beta", SystemCtl::default());
+        assert_eq!(fleet.len(), 2);
+        let result = fleet.run(|ctl| match ctl.get_path() {
+            "/usr/bin/systemctl" => Ok(ctl.get_path().len()),
+            _ => Err(std::io::Error::other("unexpected path")),
+        });
+        assert!(result.is_ok());
+        assert_eq!(result.successes().count(), 2);
+        assert_eq!(result.errors().count(), 0);
+        assert_eq!(result.get("beta").unwrap().as_ref().unwrap(), &18);
+
+        let mut calls = 0;
+        let result = fleet
+            .run(|_| -> std::io::Result<()> { Err(std::io::Error::other("unreachable host")) });
+        for (name, e) in result.errors() {
+            assert!(name == "alpha" || name == "beta");
+            assert_eq!(e.to_string(), "unreachable host");
+            calls += 1;
+        }
+        assert_eq!(calls, 2);
+        assert!(!result.is_ok());
+    }
+
+    #[test]
+    fn test_fleet_partial_failure() {
+        let up = MockExecutor::new();
+        up.on(&["restart", "cron.service"], MockResponse::success(""));
+        let down = MockExecutor::new();
+        down.on(
+            &["restart"],
+            MockResponse::failure(5, "Failed to connect to bus"),
+        );
+        let fleet = Fleet::new()
+            .with_member("web1", up.ctl())
+            .with_member("web2", down.ctl());
+        // both members must be running at once to get past the barrier
+        let barrier = Barrier::new(fleet.len());
+        let result = fleet.run(|ctl| {
+            barrier.wait();
+            ctl.restart("cron.service")
+        });
+        assert!(!result.is_ok());
+        assert!(result.get("web1").unwrap().is_ok());
+        let errors: Vec<_> = result.errors().collect();
+        assert_eq!(errors.len(), 1);
+        assert_eq!(errors[0].0, "web2");
+        assert_eq!(errors[0].1.to_string(), "Process exited with code: 5");
+        assert!(up.was_called(&["restart", "cron.service"]));
+        assert!(down.was_called(&["restart", "cron.service"]));
+    }
+}
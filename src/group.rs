@@ -0,0 +1,191 @@
+//! Grouping and sorting of unit collections
+use crate::{parse_bytes, parse_timespan, ActiveState, RunningUnit, Type, Unit, UnitList};
+use std::cmp::Ordering;
+use std::collections::BTreeMap;
+use std::time::Duration;
+
+/// A unit description that can be grouped and sorted
+pub trait UnitRecord {
+    /// Unit name
+    fn unit_name(&self) -> &str;
+
+    /// Activation state, if known
+    fn unit_active_state(&self) -> Option<ActiveState> {
+        None
+    }
+}
+
+impl UnitRecord for Unit {
+    fn unit_name(&self) -> &str {
+        &self.name
+    }
+    fn unit_active_state(&self) -> Option<ActiveState> {
+        Some(self.active_state)
+    }
+}
+
+impl UnitRecord for RunningUnit {
+    fn unit_name(&self) -> &str {
+        &self.name
+    }
+    fn unit_active_state(&self) -> Option<ActiveState> {
+        Some(self.active_state)
+    }
+}
+
+impl UnitRecord for UnitList {
+    fn unit_name(&self) -> &str {
+        &self.unit_file
+    }
+}
+
+impl UnitRecord for String {
+    fn unit_name(&self) -> &str {
+        self
+    }
+}
+
+/// Groups `units` by type. Units of unknown type are left out.
+pub fn group_by_type<T: UnitRecord>(units: &[T]) -> BTreeMap<Type, Vec<&T>> {
+    let mut groups: BTreeMap<Type, Vec<&T>> = BTreeMap::new();
+    for unit in units {
+        if let Ok(utype) = Type::from_unit_name(unit.unit_name()) {
+            groups.entry(utype).or_default().push(unit);
+        }
+    }
+    groups
+}
+
+/// Groups `units` by activation state. Units of unknown state are left out.
+pub fn group_by_state<T: UnitRecord>(units: &[T]) -> BTreeMap<ActiveState, Vec<&T>> {
+    let mut groups: BTreeMap<ActiveState, Vec<&T>> = BTreeMap::new();
+    for unit in units {
+        if let Some(state) = unit.unit_active_state() {
+            groups.entry(state).or_default().push(unit);
+        }
+    }
+    groups
+}
+
+/// Groups `units` by slice, units outside of any slice are grouped under `None`
+pub fn group_by_slice(units: &[Unit]) -> BTreeMap<Option<String>, Vec<&Unit>> {
+    let mut groups: BTreeMap<Option<String>, Vec<&Unit>> = BTreeMap::new();
+    for unit in units {
+        groups.entry(unit.slice.clone()).or_default().push(unit);
+    }
+    groups
+}
+
+/// Sort criteria of [sort_units]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+pub enum SortKey {
+    /// Alphabetical order
+    #[default]
+    Name,
+    /// Decreasing memory consumption
+    Memory,
+    /// Decreasing CPU time
+    Cpu,
+    /// Decreasing activation time
+    StartupTime,
+}
+
+/// Sorts `units` by given criteria. Units missing the sorted
+/// value are moved last, by name.
+pub fn sort_units(units: &mut [Unit], key: SortKey) {
+    // largest first, unknown last
+    fn decreasing<V: Ord>(a: Option<V>, b: Option<V>) -> Ordering {
+        match (a, b) {
+            (Some(a), Some(b)) => b.cmp(&a),
+            (Some(_), None) => Ordering::Less,
+            (None, Some(_)) => Ordering::Greater,
+            (None, None) => Ordering::Equal,
+        }
+    }
+    units.sort_by(|a, b| {
+        let order = match key {
+            SortKey::Name => Ordering::Equal,
+            SortKey::Memory => decreasing(memory_bytes(a), memory_bytes(b)),
+            SortKey::Cpu => decreasing(cpu_time(a), cpu_time(b)),
+            SortKey::StartupTime => decreasing(a.startup_time, b.startup_time),
+        };
+        order.then_with(|| a.name.cmp(&b.name))
+    });
+}
+
+/// Memory consumption of `unit` in bytes, from its [ResourceUsage](crate::ResourceUsage)
+/// or else its human readable `memory`, like `1.2M` (`systemctl status`)
+pub fn memory_bytes(unit: &Unit) -> Option<u64> {
+    if let Some(bytes) = unit.resources.memory_current {
+        return Some(bytes);
+    }
+    parse_bytes(unit.memory.as_deref()?.split_ascii_whitespace().next()?)
+}
+
+/// CPU time consumed by `unit`, from its [ResourceUsage](crate::ResourceUsage)
+/// or else its `cpu` time span, like `1.234s` (`systemctl status`)
+pub fn cpu_time(unit: &Unit) -> Option<Duration> {
+    if let Some(cpu) = unit.resources.cpu_time() {
+        return Some(cpu);
+    }
+    parse_timespan(unit.cpu.as_deref()?.trim())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn unit(name: &str, memory: Option<&str>, cpu: Option<&str>) -> Unit {
+        Unit {
+            name: name.to_string(),
+            memory: memory.map(str::to_string),
+            cpu: cpu.map(str::to_string),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_group_units() {
+        let names: Vec<String> = ["a.service", "b.timer", "c.service", "d"]
+            .iter()
+            .map(|name| name.to_string())
+            .collect();
+        let groups = group_by_type(&names);
+        assert_eq!(groups.len(), 2);
+        assert_eq!(groups[&Type::Service], vec!["a.service", "c.service"]);
+        assert_eq!(groups[&Type::Timer], vec!["b.timer"]);
+
+        let mut units = vec![unit("a.service", None, None), unit("b.service", None, None)];
+        units[0].active_state = ActiveState::Failed;
+        units[1].slice = Some("system.slice".into());
+        let groups = group_by_state(&units);
+        assert_eq!(groups[&ActiveState::Failed][0].name, "a.service");
+        assert_eq!(groups[&ActiveState::Inactive][0].name, "b.service");
+        let groups = group_by_slice(&units);
+        assert_eq!(groups[&None][0].name, "a.service");
+        assert_eq!(groups[&Some("system.slice".into())][0].name, "b.service");
+    }
+
+    #[test]
+    fn test_sort_units() {
+        let mut units = vec![
+            unit("a.service", Some("1.5M"), Some("2s")),
+            unit("b.service", None, Some("3s")),
+            unit("c.service", Some("4.0M"), None),
+        ];
+        assert_eq!(memory_bytes(&units[0]), Some(1572864));
+        assert_eq!(cpu_time(&units[1]), Some(Duration::from_secs(3)));
+
+        sort_units(&mut units, SortKey::Memory);
+        let names: Vec<&str> = units.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["c.service", "a.service", "b.service"]);
+
+        sort_units(&mut units, SortKey::Cpu);
+        let names: Vec<&str> = units.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["b.service", "a.service", "c.service"]);
+
+        sort_units(&mut units, SortKey::Name);
+        let names: Vec<&str> = units.iter().map(|u| u.name.as_str()).collect();
+        assert_eq!(names, vec!["a.service", "b.service", "c.service"]);
+    }
+}
@@ -0,0 +1,139 @@
+//! Queued jobs of the service manager (`systemctl list-jobs`)
+use crate::{RunResult, SystemCtl};
+use std::str::FromStr;
+use strum_macros::EnumString;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Kind of operation performed by a [Job]
+#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum JobType {
+    #[strum(serialize = "start")]
+    Start,
+    #[strum(serialize = "verify-active")]
+    VerifyActive,
+    #[strum(serialize = "stop")]
+    Stop,
+    #[strum(serialize = "reload")]
+    Reload,
+    #[strum(serialize = "restart")]
+    Restart,
+    #[strum(serialize = "try-restart")]
+    TryRestart,
+    #[strum(serialize = "try-reload")]
+    TryReload,
+    #[strum(serialize = "reload-or-start")]
+    ReloadOrStart,
+    #[strum(serialize = "nop")]
+    Nop,
+}
+
+/// Progress of a [Job]
+#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum JobState {
+    /// Queued, waiting for its dependencies
+    #[strum(serialize = "waiting")]
+    Waiting,
+    /// Being executed
+    #[strum(serialize = "running")]
+    Running,
+}
+
+/// A job queued in the service manager
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Job {
+    /// Job identifier, see [SystemCtl::cancel_job]
+    pub id: u32,
+    /// Unit the job operates on
+    pub unit: String,
+    /// Operation performed
+    pub job_type: JobType,
+    /// Progress of the job
+    pub state: JobState,
+}
+
+impl Job {
+    /// Parses the output of `systemctl list-jobs --no-legend --full`.
+    /// Unknown job types and states are skipped.
+    pub fn parse_list(content: &str) -> Vec<Job> {
+        content
+            .lines()
+            .filter_map(|line| {
+                let mut columns = line.split_ascii_whitespace();
+                Some(Job {
+                    id: columns.next()?.parse().ok()?,
+                    unit: columns.next()?.to_string(),
+                    job_type: JobType::from_str(columns.next()?).ok()?,
+                    state: JobState::from_str(columns.next()?).ok()?,
+                })
+            })
+            .collect()
+    }
+}
+
+impl SystemCtl {
+    /// Enqueues a start job for given `unit` without waiting
+    /// for it to complete (`systemctl start --no-block`)
+    pub fn start_nonblocking(&self, unit: impl AsRef<str>) -> std::io::Result<RunResult> {
+        self.systemctl_capture(["start", "--no-block", unit.as_ref()])
+    }
+
+    /// Enqueues a stop job for given `unit` without waiting
+    /// for it to complete (`systemctl stop --no-block`)
+    pub fn stop_nonblocking(&self, unit: impl AsRef<str>) -> std::io::Result<RunResult> {
+        self.systemctl_capture(["stop", "--no-block", unit.as_ref()])
+    }
+
+    /// Enqueues a restart job for given `unit` without waiting
+    /// for it to complete (`systemctl restart --no-block`)
+    pub fn restart_nonblocking(&self, unit: impl AsRef<str>) -> std::io::Result<RunResult> {
+        self.systemctl_capture(["restart", "--no-block", unit.as_ref()])
+    }
+
+    /// Lists the jobs currently queued (`systemctl list-jobs`)
+    pub fn list_jobs(&self) -> std::io::Result<Vec<Job>> {
+        let content = self.systemctl_capture(["list-jobs", "--no-legend", "--full"])?;
+        Ok(Job::parse_list(&content.stdout))
+    }
+
+    /// Cancels the job of given `id` (`systemctl cancel`)
+    pub fn cancel_job(&self, id: u32) -> std::io::Result<RunResult> {
+        self.systemctl_capture(["cancel", &id.to_string()])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_jobs() {
+        let content = "\
+1544 nginx.service           restart running
+1545 network-online.target   start   waiting
+1546 foo.service             unknown waiting
+No jobs running.
+";
+        assert_eq!(
+            Job::parse_list(content),
+            vec![
+                Job {
+                    id: 1544,
+                    unit: "nginx.service".into(),
+                    job_type: JobType::Restart,
+                    state: JobState::Running,
+                },
+                Job {
+                    id: 1545,
+                    unit: "network-online.target".into(),
+                    job_type: JobType::Start,
+                    state: JobState::Waiting,
+                },
+            ]
+        );
+    }
+}
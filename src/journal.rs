@@ -0,0 +1,385 @@
+//! Unit logs, read through `journalctl`
+use crate::{parse_usec, SystemCtl};
+use bon::Builder;
+use std::collections::BTreeMap;
+use std::io::{BufRead, BufReader, Error, ErrorKind};
+use std::process::{Child, Command, Stdio};
+use std::time::SystemTime;
+use strum_macros::EnumString;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const JOURNALCTL_PATH: &str = "/usr/bin/journalctl";
+
+/// Struct with API calls to journalctl.
+///
+/// Use the `::default()` impl if you don't need special arguments.
+///
+/// Use the builder API when you want to specify a custom path to journalctl binary or extra args.
+/// See [SystemCtl::journal] to read the logs of the machine a [SystemCtl] targets.
+#[derive(Builder, Default, Clone, Debug)]
+pub struct JournalCtl {
+    /// Allows passing global arguments to journalctl like `--user`.
+    additional_args: Vec<String>,
+    /// The path to the journalctl binary, by default it's [JOURNALCTL_PATH]
+    path: Option<String>,
+    /// [SystemCtl] whose `machine` target, executor, command timeout,
+    /// environment profile and pre-spawn hook also apply to `journalctl`.
+    /// Remote `host`s cannot be reached by `journalctl`.
+    #[builder(default)]
+    systemctl: SystemCtl,
+}
+
+/// Journal filters, all of them are optional
+///
+/// ```
+/// let query = systemctl::JournalQuery::builder()
+///     .unit("cron.service".to_string())
+///     .since("-1h".to_string())
+///     .priority(systemctl::Priority::Warning)
+///     .build();
+/// ```
+#[derive(Builder, Default, Clone, Debug, PartialEq)]
+pub struct JournalQuery {
+    /// Only show messages of given unit (`--unit`)
+    pub unit: Option<String>,
+    /// Only show entries more recent than given time (`--since`),
+    /// in any format understood by journalctl, like `-1h`, `yesterday`
+    /// or `@1704967200` (seconds since epoch)
+    pub since: Option<String>,
+    /// Only show entries older than given time (`--until`)
+    pub until: Option<String>,
+    /// Only show the most recent entries (`--lines`)
+    pub lines: Option<usize>,
+    /// Only show entries of given priority or more important (`--priority`)
+    pub priority: Option<Priority>,
+}
+
+impl JournalQuery {
+    /// Converts self to journalctl arguments
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(unit) = &self.unit {
+            args.push(format!("--unit={}", unit));
+        }
+        if let Some(since) = &self.since {
+            args.push(format!("--since={}", since));
+        }
+        if let Some(until) = &self.until {
+            args.push(format!("--until={}", until));
+        }
+        if let Some(lines) = self.lines {
+            args.push(format!("--lines={}", lines));
+        }
+        if let Some(priority) = self.priority {
+            args.push(format!("--priority={}", priority as u8));
+        }
+        args
+    }
+}
+
+/// Syslog priority of a journal entry, most important first
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, EnumString, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Priority {
+    #[strum(serialize = "emerg", serialize = "0")]
+    Emergency = 0,
+    #[strum(serialize = "alert", serialize = "1")]
+    Alert = 1,
+    #[strum(serialize = "crit", serialize = "2")]
+    Critical = 2,
+    #[strum(serialize = "err", serialize = "3")]
+    Error = 3,
+    #[strum(serialize = "warning", serialize = "4")]
+    Warning = 4,
+    #[strum(serialize = "notice", serialize = "5")]
+    Notice = 5,
+    #[strum(serialize = "info", serialize = "6")]
+    Info = 6,
+    #[strum(serialize = "debug", serialize = "7")]
+    Debug = 7,
+}
+
+/// A journal entry, parsed from `journalctl -o json`
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct JournalEntry {
+    /// Reception time (`__REALTIME_TIMESTAMP`), in UTC
+    pub timestamp: Option<SystemTime>,
+    /// Originating unit (`_SYSTEMD_UNIT`)
+    pub unit: Option<String>,
+    /// Message priority (`PRIORITY`)
+    pub priority: Option<Priority>,
+    /// Originating process ID (`_PID`)
+    pub pid: Option<u64>,
+    /// Human readable message (`MESSAGE`)
+    pub message: Option<String>,
+    /// All fields of this entry. Binary values are converted lossily
+    /// and fields appearing several times are joined with a newline.
+    pub fields: BTreeMap<String, String>,
+}
+
+impl JournalEntry {
+    /// Parses a single line of `journalctl -o json` output
+    pub fn parse_json(line: &str) -> std::io::Result<JournalEntry> {
+        let object: serde_json::Map<String, serde_json::Value> =
+            serde_json::from_str(line).map_err(|e| Error::new(ErrorKind::InvalidData, e))?;
+        let mut fields = BTreeMap::new();
+        for (key, value) in object {
+            if let Some(value) = json_field_value(&value) {
+                fields.insert(key, value);
+            }
+        }
+        Ok(JournalEntry {
+            timestamp: fields
+                .get("__REALTIME_TIMESTAMP")
+                .and_then(|t| parse_usec(t)),
+            unit: fields.get("_SYSTEMD_UNIT").cloned(),
+            priority: fields
+                .get("PRIORITY")
+                .and_then(|p| p.parse::<Priority>().ok()),
+            pid: fields.get("_PID").and_then(|pid| pid.parse().ok()),
+            message: fields.get("MESSAGE").cloned(),
+            fields,
+        })
+    }
+}
+
+/// Converts a journal JSON field value to a string:
+/// strings are kept, byte arrays are decoded, repeated fields are joined
+fn json_field_value(value: &serde_json::Value) -> Option<String> {
+    use serde_json::Value;
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Array(items) if items.iter().all(Value::is_u64) => {
+            let bytes: Vec<u8> = items
+                .iter()
+                .filter_map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                .collect();
+            Some(String::from_utf8_lossy(&bytes).into_owned())
+        },
+        Value::Array(items) => {
+            let items: Vec<String> = items.iter().filter_map(json_field_value).collect();
+            Some(items.join("\n"))
+        },
+        _ => None,
+    }
+}
+
+impl JournalCtl {
+    fn get_path(&self) -> &str {
+        self.path.as_deref().unwrap_or(JOURNALCTL_PATH)
+    }
+
+    /// Prepares the `journalctl -o json $query $extra` command
+    fn command(&self, query: &JournalQuery, extra: &[&str]) -> std::io::Result<Command> {
+        let ctl = &self.systemctl;
+        if ctl.host.is_some() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "journalctl cannot read the journal of a remote host",
+            ));
+        }
+        let mut command = Command::new(self.get_path());
+        command.args(&self.additional_args);
+        if let Some(machine) = &ctl.machine {
+            command.args(["-M", machine]);
+        }
+        command
+            .args(["--no-pager", "--output=json"])
+            .args(query.to_args())
+            .args(extra);
+        ctl.customize(&mut command);
+        Ok(command)
+    }
+
+    /// Returns the journal entries matching given `query`, oldest first
+    pub fn entries(&self, query: &JournalQuery) -> std::io::Result<Vec<JournalEntry>> {
+        let command = self.command(query, &[])?;
+        let output = self
+            .systemctl
+            .execute(command, self.systemctl.command_timeout)?;
+        if !output.status.success() {
+            return Err(Error::other(format!(
+                "journalctl failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .map(JournalEntry::parse_json)
+            .collect()
+    }
+
+    /// Returns the most recent `lines` entries of given `unit`
+    pub fn unit_entries(&self, unit: &str, lines: usize) -> std::io::Result<Vec<JournalEntry>> {
+        let query = JournalQuery::builder()
+            .unit(unit.to_string())
+            .lines(lines)
+            .build();
+        self.entries(&query)
+    }
+
+    /// Follows the journal (`--follow`): the returned iterator yields
+    /// the entries matching `query`, then blocks waiting for new ones.
+    /// journalctl is terminated when the iterator is dropped.
+    /// Neither the executor nor the command timeout apply, as the
+    /// entries are read at the caller's pace.
+    pub fn follow(&self, query: &JournalQuery) -> std::io::Result<JournalFollow> {
+        let mut child = self
+            .command(query, &["--follow"])?
+            .stdout(Stdio::piped())
+            .stderr(Stdio::piped())
+            .spawn()?;
+        let stdout = child
+            .stdout
+            .take()
+            .ok_or_else(|| Error::new(ErrorKind::BrokenPipe, "journalctl stdout not captured"))?;
+        Ok(JournalFollow {
+            child,
+            lines: BufReader::new(stdout).lines(),
+        })
+    }
+}
+
+impl SystemCtl {
+    /// Creates a [JournalCtl] reading the journal of the machine self targets,
+    /// with the same executor, command timeout and environment.
+    ///
+    /// ```no_run
+    /// let ctl = systemctl::SystemCtl::builder()
+    ///     .additional_args(vec![])
+    ///     .machine("debian".to_string())
+    ///     .build();
+    /// for entry in ctl.journal().unit_entries("nginx.service", 20).unwrap() {
+    ///     println!("{:?}", entry.message);
+    /// }
+    /// ```
+    pub fn journal(&self) -> JournalCtl {
+        JournalCtl {
+            additional_args: Vec::new(),
+            path: None,
+            systemctl: self.clone(),
+        }
+    }
+}
+
+/// Iterator over followed journal entries, see [JournalCtl::follow]
+#[derive(Debug)]
+pub struct JournalFollow {
+    child: Child,
+    lines: std::io::Lines<BufReader<std::process::ChildStdout>>,
+}
+
+impl Iterator for JournalFollow {
+    type Item = std::io::Result<JournalEntry>;
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.lines.next()? {
+                Ok(line) if line.trim().is_empty() => continue,
+                Ok(line) => return Some(JournalEntry::parse_json(&line)),
+                Err(e) => return Some(Err(e)),
+            }
+        }
+    }
+}
+
+impl Drop for JournalFollow {
+    fn drop(&mut self) {
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+    use std::sync::Arc;
+    use std::time::{Duration, UNIX_EPOCH};
+
+    #[test]
+    fn test_parse_journal_entry() {
+        let line = r#"{"__CURSOR":"s=abc;i=1","__REALTIME_TIMESTAMP":"1704967200000000","PRIORITY":"6","_PID":"787","_SYSTEMD_UNIT":"cron.service","MESSAGE":"(root) CMD (run-parts)","_CMDLINE":null}"#;
+        let entry = JournalEntry::parse_json(line).unwrap();
+        assert_eq!(
+            entry.timestamp,
+            Some(UNIX_EPOCH + Duration::from_secs(1704967200))
+        );
+        assert_eq!(entry.unit.as_deref(), Some("cron.service"));
+        assert_eq!(entry.priority, Some(Priority::Info));
+        assert_eq!(entry.pid, Some(787));
+        assert_eq!(entry.message.as_deref(), Some("(root) CMD (run-parts)"));
+        assert!(!entry.fields.contains_key("_CMDLINE"));
+
+        // binary message and repeated fields
+        let line = r#"{"MESSAGE":[104,105],"TAG":["a","b"]}"#;
+        let entry = JournalEntry::parse_json(line).unwrap();
+        assert_eq!(entry.message.as_deref(), Some("hi"));
+        assert_eq!(entry.fields["TAG"], "a\nb");
+
+        assert!(JournalEntry::parse_json("not json").is_err());
+    }
+
+    #[test]
+    fn test_journal_query_args() {
+        let query = JournalQuery::builder()
+            .unit("cron.service".to_string())
+            .since("-1h".to_string())
+            .lines(10)
+            .priority(Priority::Error)
+            .build();
+        assert_eq!(
+            query.to_args(),
+            vec![
+                "--unit=cron.service",
+                "--since=-1h",
+                "--lines=10",
+                "--priority=3"
+            ]
+        );
+        assert!(JournalQuery::default().to_args().is_empty());
+        assert!(Priority::Error < Priority::Warning);
+        assert_eq!("warning".parse::<Priority>(), Ok(Priority::Warning));
+    }
+
+    #[test]
+    fn test_journal_of_target() {
+        let mock = MockExecutor::new();
+        mock.on(
+            &["--unit=cron.service"],
+            MockResponse::success("{\"MESSAGE\":\"started\"}\n"),
+        );
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .machine("debian".to_string())
+            .executor(Arc::new(mock.clone()))
+            .build();
+        let entries = ctl.journal().unit_entries("cron.service", 10).unwrap();
+        assert_eq!(entries[0].message.as_deref(), Some("started"));
+        let calls = mock.calls();
+        assert_eq!(calls[0].program, JOURNALCTL_PATH);
+        assert_eq!(
+            calls[0].args,
+            [
+                "-M",
+                "debian",
+                "--no-pager",
+                "--output=json",
+                "--unit=cron.service",
+                "--lines=10"
+            ]
+        );
+
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .host("root@web1".to_string())
+            .executor(Arc::new(mock))
+            .build();
+        let e = ctl.journal().unit_entries("cron.service", 10).unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Unsupported);
+    }
+}
@@ -0,0 +1,155 @@
+//! Signal delivery to the processes of a unit (`systemctl kill`)
+use crate::{RunResult, SystemCtl};
+use bon::Builder;
+use strum_macros::EnumString;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Signal to deliver with [SystemCtl::kill]
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Signal {
+    /// `SIGHUP`, usually asks daemons to reload their configuration
+    Hup,
+    /// `SIGINT`
+    Int,
+    /// `SIGQUIT`
+    Quit,
+    /// `SIGABRT`
+    Abrt,
+    /// `SIGKILL`, cannot be caught
+    Kill,
+    /// `SIGUSR1`
+    Usr1,
+    /// `SIGUSR2`
+    Usr2,
+    /// `SIGTERM`, the default
+    #[default]
+    Term,
+    /// `SIGCONT`
+    Cont,
+    /// `SIGSTOP`, cannot be caught
+    Stop,
+    /// `SIGWINCH`
+    Winch,
+    /// Any signal, by number (libc value)
+    Number(i32),
+}
+
+impl Signal {
+    /// `--signal` value
+    fn as_arg(&self) -> String {
+        let name = match self {
+            Self::Hup => "SIGHUP",
+            Self::Int => "SIGINT",
+            Self::Quit => "SIGQUIT",
+            Self::Abrt => "SIGABRT",
+            Self::Kill => "SIGKILL",
+            Self::Usr1 => "SIGUSR1",
+            Self::Usr2 => "SIGUSR2",
+            Self::Term => "SIGTERM",
+            Self::Cont => "SIGCONT",
+            Self::Stop => "SIGSTOP",
+            Self::Winch => "SIGWINCH",
+            Self::Number(number) => return number.to_string(),
+        };
+        name.to_string()
+    }
+}
+
+/// Processes of a unit receiving the signal (`--kill-whom`)
+#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum KillWhom {
+    /// Main process only
+    #[strum(serialize = "main")]
+    Main,
+    /// Control process only (`ExecReload=`, `ExecStop=`.. commands)
+    #[strum(serialize = "control")]
+    Control,
+    /// All processes of the unit, the default
+    #[strum(serialize = "all")]
+    #[default]
+    All,
+}
+
+impl KillWhom {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            Self::Main => "main",
+            Self::Control => "control",
+            Self::All => "all",
+        }
+    }
+}
+
+/// Options of [SystemCtl::kill], defaults to `SIGTERM` on all processes
+///
+/// ```no_run
+/// use systemctl::{KillOptions, KillWhom, Signal, SystemCtl};
+/// let ctl = SystemCtl::default();
+/// let options = KillOptions::builder()
+///     .signal(Signal::Hup)
+///     .whom(KillWhom::Main)
+///     .build();
+/// ctl.kill("nginx.service", &options).unwrap();
+/// ```
+#[derive(Builder, Default, Clone, Debug, PartialEq)]
+pub struct KillOptions {
+    /// Signal to deliver (`--signal`)
+    pub signal: Option<Signal>,
+    /// Processes receiving the signal (`--kill-whom`)
+    pub whom: Option<KillWhom>,
+}
+
+impl KillOptions {
+    /// Converts self to systemctl arguments
+    fn to_args(&self) -> Vec<String> {
+        let mut args = Vec::new();
+        if let Some(signal) = &self.signal {
+            args.push(format!("--signal={}", signal.as_arg()));
+        }
+        if let Some(whom) = &self.whom {
+            args.push(format!("--kill-whom={}", whom.as_arg()));
+        }
+        args
+    }
+}
+
+impl SystemCtl {
+    /// Sends a signal to the processes of given `unit` (`systemctl kill`)
+    pub fn kill(&self, unit: &str, options: &KillOptions) -> std::io::Result<RunResult> {
+        let args = options.to_args();
+        let mut args: Vec<&str> = args.iter().map(String::as_str).collect();
+        args.insert(0, "kill");
+        args.push(unit);
+        self.systemctl_capture(args)
+    }
+
+    /// Resets the failed state of given `unit`, as well as its
+    /// start rate limiting counter (`systemctl reset-failed`)
+    pub fn reset_failed(&self, unit: &str) -> std::io::Result<RunResult> {
+        self.systemctl_capture(["reset-failed", unit])
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_kill_args() {
+        assert!(KillOptions::default().to_args().is_empty());
+        let options = KillOptions::builder()
+            .signal(Signal::Hup)
+            .whom(KillWhom::Main)
+            .build();
+        assert_eq!(
+            options.to_args(),
+            vec!["--signal=SIGHUP", "--kill-whom=main"]
+        );
+        let options = KillOptions::builder().signal(Signal::Number(35)).build();
+        assert_eq!(options.to_args(), vec!["--signal=35"]);
+    }
+}
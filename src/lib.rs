@@ -1,16 +1,146 @@
 //! Crate to manage and monitor services through `systemctl`   
 //! Homepage: <https://github.com/gwbres/systemctl>
 #![doc=include_str!("../README.md")]
-use std::io::{Error, ErrorKind, Read};
-use std::process::{Child, ExitStatus};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::process::{Child, Command, ExitStatus};
 use std::str::FromStr;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
+use std::time::{Duration, SystemTime};
 use strum_macros::EnumString;
 
+mod activation;
+mod analyze;
+mod ask_password;
+mod backend;
+mod credentials;
+#[cfg(feature = "dbus")]
+mod dbus;
+mod dependencies;
+mod discovery;
+mod env_profile;
+mod environment;
+mod executor;
+mod exit_status;
+mod fleet;
+mod group;
+mod jobs;
+mod journal;
+mod kill;
+mod lifecycle;
+mod machine;
+#[cfg(any(test, feature = "test-util"))]
+mod mock;
+mod parse_error;
+mod property;
+mod queue;
+mod readiness;
+mod resources;
+mod running;
+mod start_limit;
+mod stream;
+mod support;
+mod thresholds;
+mod timeout;
+mod timer;
+mod timestamp;
+mod transaction;
+mod unit_file;
+mod unit_name;
+mod warning;
+mod watch;
+pub use activation::ActivationSource;
+pub use analyze::{ConditionCheck, ConditionResult};
+pub use ask_password::{AskPasswordQuery, PasswordRequired};
+pub use backend::Backend;
+pub use credentials::{Credential, CredentialSource, Credentials};
+#[cfg(feature = "dbus")]
+pub use dbus::DbusBackend;
+pub use dependencies::{DependencyIter, DependencyKind, DependencyNode};
+pub use discovery::{discover_units, discover_units_in, DiscoveredUnit, SYSTEM_UNIT_DIRS};
+pub use env_profile::EnvProfile;
+pub use executor::{ProcessExecutor, SystemctlExecutor};
+pub use exit_status::{exit_status_class, exit_status_code, exit_status_name, ExitStatusClass};
+pub use fleet::{Fleet, FleetResult};
+pub use group::{
+    cpu_time, group_by_slice, group_by_state, group_by_type, memory_bytes, sort_units, SortKey,
+    UnitRecord,
+};
+pub use jobs::{Job, JobState, JobType};
+pub use journal::{JournalCtl, JournalEntry, JournalFollow, JournalQuery, Priority};
+pub use kill::{KillOptions, KillWhom, Signal};
+pub use lifecycle::{PresetMode, UnitFileOptions};
+pub use machine::Machine;
+#[cfg(any(test, feature = "test-util"))]
+pub use mock::{Invocation, MockExecutor, MockResponse};
+pub use parse_error::ParseError;
+use parse_error::Strictness;
+pub use property::Property;
+pub use queue::{TaskHandle, TaskQueue};
+pub use readiness::StartOutcome;
+pub use resources::{parse_bytes, ResourceUsage};
+pub use running::RunningUnit;
+pub use start_limit::StartLimit;
+pub use stream::LineStream;
+pub use support::SupportBundle;
+pub use thresholds::{AlertState, Metric, Threshold, ThresholdAlert, WatchEvent};
+pub use timeout::CommandTimedOut;
+pub use timer::TimerEntry;
+pub use timestamp::{format_utc_timestamp, parse_timespan, parse_usec, parse_utc_timestamp};
+pub use transaction::{Operation, Transaction, TransactionError, UnitSnapshot};
+pub use unit_file::{RestartPolicy, Scope, ServiceType, UnitFile, UnitFileBuilder};
+pub use unit_name::{escape, escape_path, unescape, UnitName};
+pub use warning::Warning;
+pub use watch::{StateChange, UnitState, UnitWatcher};
+
 #[cfg(feature = "serde")]
 use serde::{Deserialize, Serialize};
 
 const SYSTEMCTL_PATH: &str = "/usr/bin/systemctl";
 
+/// Properties queried with `systemctl show` to build a [Unit]
+const UNIT_PROPERTIES: &[&str] = &[
+    "Id",
+    "Description",
+    "LoadState",
+    "ActiveState",
+    "UnitFileState",
+    "UnitFilePreset",
+    "FragmentPath",
+    "Transient",
+    "Restart",
+    "KillMode",
+    "MainPID",
+    "ControlPID",
+    "TasksCurrent",
+    "CPUUsageNSec",
+    "MemoryCurrent",
+    "MemoryPeak",
+    "MemorySwapCurrent",
+    "IPIngressBytes",
+    "IPEgressBytes",
+    "IOReadBytes",
+    "IOWriteBytes",
+    "What",
+    "Where",
+    "Documentation",
+    "Wants",
+    "WantedBy",
+    "RequiredBy",
+    "TriggeredBy",
+    "Before",
+    "After",
+    "ExecStart",
+    "ExecReload",
+    "StateChangeTimestamp",
+    "NextElapseUSecRealtime",
+    "Slice",
+    "InactiveExitTimestampMonotonic",
+    "ActiveEnterTimestampMonotonic",
+];
+
 use bon::Builder;
 
 /// Struct with API calls to systemctl.
@@ -24,38 +154,290 @@ pub struct SystemCtl {
     additional_args: Vec<String>,
     /// The path to the systemctl binary, by default it's [SYSTEMCTL_PATH]
     path: Option<String>,
+    /// Passes `--no-ask-password`: operations requiring interactive
+    /// authentication fail with a [PasswordRequired] error instead of prompting.
+    #[builder(default)]
+    no_ask_password: bool,
+    /// Operates on a remote host (`--host`), like `user@host`
+    /// or `user@host:container`
+    host: Option<String>,
+    /// Operates on a local container (`--machine`), like `user@container`.
+    /// Commands fail with [ErrorKind::InvalidInput] when `host` is set as well.
+    machine: Option<String>,
+    /// Fails with a [ParseError] on unrecognized `systemctl` outputs
+    /// instead of tolerating them, to catch parser gaps in tests.
+    /// See [Unit::parse_status_strict] and [Unit::parse_show_strict].
+    #[builder(default)]
+    strict: bool,
+    /// Environment of every spawned command, inherited from
+    /// the host process by default. See [EnvProfile].
+    env_profile: Option<EnvProfile>,
+    /// Customizes every spawned command (uid/gid, environment, nice level..)
+    /// right before it is spawned
+    #[builder(into)]
+    pre_spawn: Option<CommandHook>,
+    /// Kills spawned commands lasting longer than given duration,
+    /// which then fail with a [CommandTimedOut] error. Unbounded by default,
+    /// see [SystemCtl::with_timeout] to override it for a single call.
+    command_timeout: Option<Duration>,
+    /// Runs every spawned command, see [SystemctlExecutor].
+    /// Local processes are spawned by default.
+    executor: Option<Arc<dyn SystemctlExecutor>>,
+    /// Serves frequent operations without spawning `systemctl`,
+    /// like the `DbusBackend` of the `dbus` feature. See [Backend].
+    /// Calls served by a backend fail with [ErrorKind::Unsupported]
+    /// when `host`, `machine` or `--user` is set as well.
+    backend: Option<Arc<dyn Backend>>,
+    /// Set once `systemctl` rejected `--timestamp=utc`, see [SystemCtl::systemctl_capture_utc]
+    #[builder(skip)]
+    legacy_timestamps: Arc<AtomicBool>,
+}
+
+/// Closure receiving each [Command] right before it is spawned,
+/// for settings this crate does not model.
+///
+/// ```
+/// use std::os::unix::process::CommandExt;
+/// use std::process::Command;
+///
+/// let ctl = systemctl::SystemCtl::builder()
+///     .additional_args(vec![])
+///     .pre_spawn(|cmd: &mut Command| {
+///         cmd.env("SYSTEMD_LOG_LEVEL", "debug").uid(0);
+///     })
+///     .build();
+/// ```
+#[derive(Clone)]
+pub struct CommandHook(Arc<dyn Fn(&mut Command) + Send + Sync>);
+
+impl CommandHook {
+    /// Wraps given closure
+    pub fn new<F: Fn(&mut Command) + Send + Sync + 'static>(hook: F) -> Self {
+        Self(Arc::new(hook))
+    }
+
+    /// Applies this hook to given command
+    pub fn apply(&self, command: &mut Command) {
+        (self.0)(command)
+    }
+}
+
+impl<F: Fn(&mut Command) + Send + Sync + 'static> From<F> for CommandHook {
+    fn from(hook: F) -> Self {
+        Self::new(hook)
+    }
+}
+
+impl std::fmt::Debug for CommandHook {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str("CommandHook")
+    }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
 pub struct RunResult {
     pub stdout: String,
     pub stderr: String,
     pub exit_status: std::process::ExitStatus,
+    /// Well-known warnings found in `stderr`
+    pub warnings: Vec<Warning>,
+}
+
+impl RunResult {
+    /// Returns `true` if a unit file changed on disk
+    /// and `systemctl daemon-reload` should be invoked
+    pub fn needs_daemon_reload(&self) -> bool {
+        self.warnings
+            .iter()
+            .any(|w| matches!(w, Warning::UnitFileChanged { .. }))
+    }
+}
+
+/// Converts failures reported by the `systemctl` exit code of `ctl` to errors.
+/// Codes 1 and 3 are not failures, they reflect the queried unit state,
+/// unless authentication was required or the unit is masked.
+fn check_exit_status(
+    ctl: &SystemCtl,
+    exit_status: ExitStatus,
+    stderr: &str,
+) -> std::io::Result<()> {
+    if !exit_status.success() && PasswordRequired::matches(stderr) {
+        return Err(Error::new(
+            ErrorKind::PermissionDenied,
+            PasswordRequired::new(ctl, stderr.trim()),
+        ));
+    }
+    // systemctl refuses to start masked units, exiting with code 1
+    if !exit_status.success() && stderr.contains(" is masked") {
+        return Err(Error::new(ErrorKind::PermissionDenied, stderr.trim()));
+    }
+    match exit_status.code() {
+        Some(0) => {}, // success
+        Some(1) => {}, // success -> Ok(Unit not found)
+        Some(3) => {}, // success -> Ok(unit is inactive and/or dead)
+        Some(4) => {
+            return Err(Error::new(
+                ErrorKind::PermissionDenied,
+                "Missing Priviledges or Unit not found",
+            ))
+        },
+        // unknown errorcodes
+        Some(code) => {
+            // TODO: Maybe a better ErrorKind, none really seem to fit
+            return Err(Error::other(format!("Process exited with code: {code}")));
+        },
+        None => {
+            return Err(Error::new(
+                ErrorKind::Interrupted,
+                "Process terminated by signal",
+            ))
+        },
+    }
+    Ok(())
 }
 
 impl SystemCtl {
-    /// Invokes `systemctl $args`
+    /// Prepares the `systemctl $args` command
+    fn command<'a, 's: 'a, S: IntoIterator<Item = &'a str>>(&'s self, args: S) -> Command {
+        let no_ask_password = self.no_ask_password.then_some("--no-ask-password");
+        let mut command = Command::new(self.get_path());
+        command
+            .args(self.additional_args.iter().map(String::as_str))
+            .args(self.target_args())
+            .args(no_ask_password)
+            .args(args);
+        self.customize(&mut command);
+        command
+    }
+
+    /// Spawns `systemctl $args` with piped outputs, bypassing the executor
     fn spawn_child<'a, 's: 'a, S: IntoIterator<Item = &'a str>>(
         &'s self,
         args: S,
     ) -> std::io::Result<Child> {
-        std::process::Command::new(self.get_path())
-            .args(self.additional_args.iter().map(String::as_str).chain(args))
+        self.check_target()?;
+        self.command(args)
             .stdout(std::process::Stdio::piped())
             .stderr(std::process::Stdio::piped())
             .spawn()
     }
 
+    /// Applies the [EnvProfile] then the [CommandHook], if any, on given command
+    fn customize(&self, command: &mut Command) {
+        if let Some(profile) = &self.env_profile {
+            profile.apply(command);
+        }
+        if let Some(hook) = &self.pre_spawn {
+            hook.apply(command);
+        }
+    }
+
     fn get_path(&self) -> &str {
         self.path.as_deref().unwrap_or(SYSTEMCTL_PATH)
     }
 
+    /// `--host`/`--machine` arguments, shared by all systemd tools
+    fn target_args(&self) -> Vec<&str> {
+        let mut args = Vec::new();
+        if let Some(host) = &self.host {
+            args.push("-H");
+            args.push(host.as_str());
+        }
+        if let Some(machine) = &self.machine {
+            args.push("-M");
+            args.push(machine.as_str());
+        }
+        args
+    }
+
+    /// Rejects a `host` combined with a `machine`: systemd tools only
+    /// honor one of them, the container of a remote host is selected
+    /// with `host` alone, like `user@host:container`
+    fn check_target(&self) -> std::io::Result<()> {
+        if self.host.is_some() && self.machine.is_some() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "host and machine cannot both be set, use host=\"user@host:container\"",
+            ));
+        }
+        Ok(())
+    }
+
+    /// Configured [Backend], if any. Backends reach the manager of their own
+    /// connection, they cannot honor `host`, `machine` nor `--user`:
+    /// that combination is rejected rather than silently operating
+    /// on the local system manager.
+    fn backend(&self) -> std::io::Result<Option<&dyn Backend>> {
+        let Some(backend) = &self.backend else {
+            return Ok(None);
+        };
+        if self.host.is_some()
+            || self.machine.is_some()
+            || self.additional_args.iter().any(|arg| arg == "--user")
+        {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "backends cannot target a host, a machine or the user manager",
+            ));
+        }
+        Ok(Some(backend.as_ref()))
+    }
+
+    /// Local directory holding administrator unit files and drop-ins:
+    /// `/etc/systemd/system`, or `/run/systemd/system` for `runtime` changes
+    /// lost on reboot. User directories are used with `--user`.
+    fn unit_dir(&self, runtime: bool) -> std::io::Result<PathBuf> {
+        if self.host.is_some() || self.machine.is_some() {
+            return Err(Error::new(
+                ErrorKind::Unsupported,
+                "unit files can only be written on the local host",
+            ));
+        }
+        if !self.additional_args.iter().any(|arg| arg == "--user") {
+            return Ok(PathBuf::from(if runtime {
+                "/run/systemd/system"
+            } else {
+                "/etc/systemd/system"
+            }));
+        }
+        let var = |name: &str| std::env::var_os(name).filter(|v| !v.is_empty());
+        let base = if runtime {
+            var("XDG_RUNTIME_DIR").map(PathBuf::from)
+        } else {
+            var("XDG_CONFIG_HOME")
+                .map(PathBuf::from)
+                .or_else(|| var("HOME").map(|home| PathBuf::from(home).join(".config")))
+        };
+        let base = base
+            .ok_or_else(|| Error::new(ErrorKind::NotFound, "user unit directory is not defined"))?;
+        Ok(base.join("systemd").join("user"))
+    }
+
+    /// Returns a copy of self using given `command_timeout`, `None`
+    /// waiting forever, for calls needing a specific bound.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// let ctl = systemctl::SystemCtl::default();
+    /// ctl.with_timeout(Some(Duration::from_secs(90)))
+    ///     .restart("postgresql.service")
+    ///     .unwrap();
+    /// ```
+    pub fn with_timeout(&self, timeout: Option<Duration>) -> Self {
+        Self {
+            command_timeout: timeout,
+            ..self.clone()
+        }
+    }
+
     /// Invokes `systemctl $args` silently
+    #[allow(dead_code)]
     fn systemctl<'a, 's: 'a, S: IntoIterator<Item = &'a str>>(
         &'s self,
         args: S,
     ) -> std::io::Result<ExitStatus> {
-        self.spawn_child(args)?.wait()
+        let output = self.execute(self.command(args), self.command_timeout)?;
+        Ok(output.status)
     }
 
     /// Invokes `systemctl $args` and captures stdout stream
@@ -63,44 +445,42 @@ impl SystemCtl {
         &'s self,
         args: S,
     ) -> std::io::Result<RunResult> {
-        let mut child = self.spawn_child(args)?;
-        let exit_status = child.wait()?;
-        match exit_status.code() {
-            Some(0) => {}, // success
-            Some(1) => {}, // success -> Ok(Unit not found)
-            Some(3) => {}, // success -> Ok(unit is inactive and/or dead)
-            Some(4) => {
-                return Err(Error::new(
-                    ErrorKind::PermissionDenied,
-                    "Missing Priviledges or Unit not found",
-                ))
-            },
-            // unknown errorcodes
-            Some(code) => {
-                return Err(Error::new(
-                    // TODO: Maybe a better ErrorKind, none really seem to fit
-                    ErrorKind::Other,
-                    format!("Process exited with code: {code}"),
-                ));
-            },
-            None => {
-                return Err(Error::new(
-                    ErrorKind::Interrupted,
-                    "Process terminated by signal",
-                ))
-            },
-        }
+        self.capture(self.command(args))
+    }
 
-        let mut stdout = String::new();
-        child.stdout.unwrap().read_to_string(&mut stdout).unwrap();
+    /// Invokes `systemctl $verb --timestamp=utc $args` and captures stdout stream.
+    /// systemd older than 248 does not know `--timestamp`: the command is then
+    /// run without it but in the UTC timezone, which prints the same timestamps.
+    fn systemctl_capture_utc(&self, verb: &str, args: &[&str]) -> std::io::Result<RunResult> {
+        if !self.legacy_timestamps.load(Ordering::Relaxed) {
+            let args = [verb, "--timestamp=utc"]
+                .into_iter()
+                .chain(args.iter().copied());
+            let result = self.systemctl_capture(args)?;
+            if result.exit_status.success() || !result.stderr.contains("--timestamp") {
+                return Ok(result);
+            }
+            self.legacy_timestamps.store(true, Ordering::Relaxed);
+        }
+        let mut command = self.command([verb].into_iter().chain(args.iter().copied()));
+        command.env("TZ", "UTC");
+        self.capture(command)
+    }
 
-        let mut stderr = String::new();
-        child.stderr.unwrap().read_to_string(&mut stderr).unwrap();
+    /// Runs given `systemctl` command and captures its outputs
+    fn capture(&self, command: Command) -> std::io::Result<RunResult> {
+        let output = self.execute(command, self.command_timeout)?;
+        let exit_status = output.status;
+        let stdout = String::from_utf8_lossy(&output.stdout).into_owned();
+        let stderr = String::from_utf8_lossy(&output.stderr).into_owned();
 
+        check_exit_status(self, exit_status, &stderr)?;
+        let warnings = Warning::parse(&stderr);
         Ok(RunResult {
             stdout,
             stderr,
             exit_status,
+            warnings,
         })
     }
 
@@ -110,17 +490,29 @@ impl SystemCtl {
     }
 
     /// Forces given `unit` to (re)start
-    pub fn restart(&self, unit: &str) -> std::io::Result<RunResult> {
+    pub fn restart(&self, unit: impl AsRef<str>) -> std::io::Result<RunResult> {
+        let unit = unit.as_ref();
+        if let Some(backend) = self.backend()? {
+            return backend.restart(unit);
+        }
         self.systemctl_capture(["restart", unit])
     }
 
     /// Forces given `unit` to start
-    pub fn start(&self, unit: &str) -> std::io::Result<RunResult> {
+    pub fn start(&self, unit: impl AsRef<str>) -> std::io::Result<RunResult> {
+        let unit = unit.as_ref();
+        if let Some(backend) = self.backend()? {
+            return backend.start(unit);
+        }
         self.systemctl_capture(["start", unit])
     }
 
     /// Forces given `unit` to stop
-    pub fn stop(&self, unit: &str) -> std::io::Result<RunResult> {
+    pub fn stop(&self, unit: impl AsRef<str>) -> std::io::Result<RunResult> {
+        let unit = unit.as_ref();
+        if let Some(backend) = self.backend()? {
+            return backend.stop(unit);
+        }
         self.systemctl_capture(["stop", unit])
     }
 
@@ -131,6 +523,9 @@ impl SystemCtl {
 
     /// Triggers reload for given `unit`
     pub fn reload(&self, unit: &str) -> std::io::Result<RunResult> {
+        if let Some(backend) = self.backend()? {
+            return backend.reload(unit);
+        }
         self.systemctl_capture(["reload", unit])
     }
 
@@ -161,10 +556,25 @@ impl SystemCtl {
 
     /// Returns `true` if given `unit` is actively running
     pub fn is_active(&self, unit: &str) -> std::io::Result<bool> {
+        if let Some(backend) = self.backend()? {
+            return backend.is_active(unit);
+        }
         let status = self.systemctl_capture(["is-active", unit])?;
         Ok(status.stdout.trim_end().eq("active"))
     }
 
+    /// Returns the activation state of given `unit` (`systemctl is-active`)
+    pub fn active_state(&self, unit: &str) -> std::io::Result<ActiveState> {
+        let status = self.systemctl_capture(["is-active", unit])?;
+        parse_state(unit, &status)
+    }
+
+    /// Returns the enablement state of given `unit` (`systemctl is-enabled`)
+    pub fn enablement_state(&self, unit: &str) -> std::io::Result<EnablementState> {
+        let status = self.systemctl_capture(["is-enabled", unit])?;
+        parse_state(unit, &status)
+    }
+
     /// Isolates given unit, only self and its dependencies are
     /// now actively running
     pub fn isolate(&self, unit: &str) -> std::io::Result<RunResult> {
@@ -201,6 +611,9 @@ impl SystemCtl {
         state_filter: Option<&str>,
         glob: Option<&str>,
     ) -> std::io::Result<Vec<UnitList>> {
+        if let Some(backend) = self.backend()? {
+            return backend.list_unit_files(type_filter, state_filter, glob);
+        }
         let mut args = vec!["list-unit-files"];
         if let Some(filter) = type_filter {
             args.push("--type");
@@ -261,141 +674,65 @@ impl SystemCtl {
         self.list_units(Some("service"), Some("enabled"), None)
     }
 
+    /// Returns the requested `properties` of given `unit`,
+    /// parsed from the stable `KEY=VALUE` output of `systemctl show -p ...`.
+    /// All properties are returned when `properties` is empty.
+    /// Timestamps are expressed in UTC (`--timestamp=utc`).
+    pub fn show(
+        &self,
+        unit: &str,
+        properties: &[&str],
+    ) -> std::io::Result<HashMap<String, String>> {
+        if let Some(backend) = self.backend()? {
+            return backend.show(unit, properties);
+        }
+        let content = self.show_raw(unit, properties)?;
+        Ok(parse_properties(&content.stdout))
+    }
+
+    /// Invokes `systemctl show` and returns its raw output
+    fn show_raw(&self, unit: &str, properties: &[&str]) -> std::io::Result<RunResult> {
+        let properties = properties.join(",");
+        let mut args = Vec::new();
+        if !properties.is_empty() {
+            args.push("-p");
+            args.push(&properties);
+        }
+        args.push(unit);
+        self.systemctl_capture_utc("show", &args)
+    }
+
     /// Builds a new `Unit` structure by retrieving
-    /// structure attributes with a `systemctl status $unit` call
-    pub fn create_unit(&self, name: &str) -> std::io::Result<Unit> {
-        if let Ok(false) = self.exists(name) {
+    /// structure attributes with a `systemctl show $unit` call.
+    /// Accepts plain names as well as [UnitName]s.
+    pub fn create_unit(&self, name: impl AsRef<str>) -> std::io::Result<Unit> {
+        let name = name.as_ref();
+        let properties = self.show(name, UNIT_PROPERTIES)?;
+        let load_state = properties.get("LoadState").map(String::as_str);
+        if matches!(load_state, None | Some("not-found")) {
             return Err(Error::new(
                 ErrorKind::NotFound,
                 format!("Unit or service \"{}\" does not exist", name),
             ));
         }
-        let mut u = Unit::default();
-        let status = self.status(name)?;
-        let mut lines = status.stdout.lines();
-        let next = lines.next().unwrap();
-        let (_, rem) = next.split_at(3);
-        let mut items = rem.split_ascii_whitespace();
-        let name_raw = items.next().unwrap().trim();
-        if let Some(delim) = items.next() {
-            if delim.trim().eq("-") {
-                // --> description string is provided
-                let items: Vec<_> = items.collect();
-                u.description = Some(itertools::join(&items, " "));
-            }
-        }
-        let (name, utype_raw) = name_raw
-            .rsplit_once('.')
-            .expect("Unit is missing a Type, this should not happen!");
-        // `type` is deduced from .extension
-        u.utype = match Type::from_str(utype_raw) {
-            Ok(t) => t,
-            Err(e) => panic!("For {:?} -> {e}", name_raw),
-        };
-        let mut is_doc = false;
-        for line in lines {
-            let line = line.trim_start();
-            if let Some(line) = line.strip_prefix("Loaded: ") {
-                // Match and get rid of "Loaded: "
-                if let Some(line) = line.strip_prefix("loaded ") {
-                    u.state = State::Loaded;
-                    let line = line.strip_prefix('(').unwrap();
-                    let line = line.strip_suffix(')').unwrap();
-                    let items: Vec<&str> = line.split(';').collect();
-                    u.script = items[0].trim().to_string();
-                    u.auto_start = match AutoStartStatus::from_str(items[1].trim()) {
-                        Ok(x) => x,
-                        Err(_) => AutoStartStatus::Disabled,
-                    };
-                    if items.len() > 2 {
-                        // preset is optionnal ?
-                        u.preset = items[2].trim().ends_with("enabled");
-                    }
-                } else if line.starts_with("masked") {
-                    u.state = State::Masked;
-                }
-            } else if let Some(line) = line.strip_prefix("Transient: ") {
-                if line == "yes" {
-                    u.transient = true
-                }
-            } else if line.starts_with("Active: ") {
-                // skip that one
-                // we already have .active() .inative() methods
-                // to access this information
-            } else if let Some(line) = line.strip_prefix("Docs: ") {
-                is_doc = true;
-                if let Ok(doc) = Doc::from_str(line) {
-                    u.docs.get_or_insert_with(Vec::new).push(doc);
-                }
-            } else if let Some(line) = line.strip_prefix("What: ") {
-                // mountpoint infos
-                u.mounted = Some(line.to_string())
-            } else if let Some(line) = line.strip_prefix("Where: ") {
-                // mountpoint infos
-                u.mountpoint = Some(line.to_string());
-            } else if let Some(line) = line.strip_prefix("Main PID: ") {
-                // example -> Main PID: 787 (gpm)
-                if let Some((pid, proc)) = line.split_once(' ') {
-                    u.pid = Some(pid.parse::<u64>().unwrap_or(0));
-                    u.process = Some(proc.replace(&['(', ')'][..], ""));
-                };
-            } else if let Some(line) = line.strip_prefix("Cntrl PID: ") {
-                // example -> Main PID: 787 (gpm)
-                if let Some((pid, proc)) = line.split_once(' ') {
-                    u.pid = Some(pid.parse::<u64>().unwrap_or(0));
-                    u.process = Some(proc.replace(&['(', ')'][..], ""));
-                };
-            } else if line.starts_with("Process: ") {
-                //TODO: implement
-                //TODO: parse as a Process item
-                //let items : Vec<_> = line.split_ascii_whitespace().collect();
-                //let proc_pid = u64::from_str_radix(items[1].trim(), 10).unwrap();
-                //let cli;
-                //Process: 640 ExecStartPre=/usr/sbin/sshd -t (code=exited, status=0/SUCCESS)
-            } else if line.starts_with("CGroup: ") {
-                //TODO: implement
-                //LINE: "CGroup: /system.slice/sshd.service"
-                //LINE: "└─1050 /usr/sbin/sshd -D"
-            } else if line.starts_with("Tasks: ") {
-                //TODO: implement
-            } else if let Some(line) = line.strip_prefix("Memory: ") {
-                u.memory = Some(line.trim().to_string());
-            } else if let Some(line) = line.strip_prefix("CPU: ") {
-                u.cpu = Some(line.trim().to_string())
-            } else {
-                // handling multi line cases
-                if is_doc {
-                    let line = line.trim_start();
-                    if let Ok(doc) = Doc::from_str(line) {
-                        u.docs.get_or_insert_with(Vec::new).push(doc);
-                    }
-                }
-            }
-        }
+        let mut u = Unit::from_properties(
+            properties.iter().map(|(k, v)| (k.as_str(), v.as_str())),
+            self.strict,
+        )?;
 
+        // install section is not exposed by `show`
         if let Ok(content) = self.cat(name) {
-            let line_tuple = content
+            let also = content
                 .stdout
                 .lines()
-                .filter_map(|line| line.split_once('=').to_owned());
-            for (k, v) in line_tuple {
-                let val = v.to_string();
-                match k {
-                    "Wants" => u.wants.get_or_insert_with(Vec::new).push(val),
-                    "WantedBy" => u.wanted_by.get_or_insert_with(Vec::new).push(val),
-                    "Also" => u.also.get_or_insert_with(Vec::new).push(val),
-                    "Before" => u.before.get_or_insert_with(Vec::new).push(val),
-                    "After" => u.after.get_or_insert_with(Vec::new).push(val),
-                    "ExecStart" => u.exec_start = Some(val),
-                    "ExecReload" => u.exec_reload = Some(val),
-                    "Restart" => u.restart_policy = Some(val),
-                    "KillMode" => u.kill_mode = Some(val),
-                    _ => {},
-                }
+                .filter_map(|line| line.strip_prefix("Also="));
+            for val in also {
+                u.also
+                    .get_or_insert_with(Vec::new)
+                    .extend(split_unit_list(val));
             }
         }
 
-        u.active = self.is_active(name)?;
         u.name = name.to_string();
         Ok(u)
     }
@@ -436,7 +773,7 @@ pub enum AutoStartStatus {
 }
 
 /// `Type` describes a Unit declaration Type in systemd
-#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug, Default)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
 pub enum Type {
     #[strum(serialize = "automount")]
@@ -460,6 +797,20 @@ pub enum Type {
     Target,
 }
 
+impl Type {
+    /// Deduces the `Type` from the extension of given unit name
+    pub fn from_unit_name(name: &str) -> std::io::Result<Type> {
+        let (_, utype) = name.rsplit_once('.').ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidData,
+                format!("unit \"{}\" is missing a type", name),
+            )
+        })?;
+        Type::from_str(utype)
+            .map_err(|e| Error::new(ErrorKind::InvalidData, format!("unit \"{}\": {}", name, e)))
+    }
+}
+
 /// `State` describes a Unit current state
 #[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug, Default)]
 #[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
@@ -471,6 +822,117 @@ pub enum State {
     Loaded,
 }
 
+/// `ActiveState` describes the activation state of a Unit
+/// (`systemctl is-active`)
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, EnumString, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ActiveState {
+    /// Started, bound, plugged in.. depending on the unit type
+    #[strum(serialize = "active")]
+    Active,
+    /// Active and reloading its configuration
+    #[strum(serialize = "reloading")]
+    Reloading,
+    /// Stopped, unbound, unplugged..
+    #[strum(serialize = "inactive")]
+    #[default]
+    Inactive,
+    /// Inactive after a failure (crash, timeout, exit code..)
+    #[strum(serialize = "failed")]
+    Failed,
+    /// Changing from inactive to active
+    #[strum(serialize = "activating")]
+    Activating,
+    /// Changing from active to inactive
+    #[strum(serialize = "deactivating")]
+    Deactivating,
+    /// Inactive while maintenance operations run (`systemctl clean`..)
+    #[strum(serialize = "maintenance")]
+    Maintenance,
+    /// Active while new mounts are propagated to the unit
+    #[strum(serialize = "refreshing")]
+    Refreshing,
+}
+
+impl ActiveState {
+    /// Returns `true` for states `systemctl is-active` reports as successful:
+    /// active, reloading and refreshing
+    pub fn is_active(&self) -> bool {
+        matches!(self, Self::Active | Self::Reloading | Self::Refreshing)
+    }
+}
+
+/// `EnablementState` describes how a Unit file is installed
+/// (`systemctl is-enabled`)
+#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum EnablementState {
+    /// Enabled through `.wants/`, `.requires/` symlinks or aliases in `/etc`
+    #[strum(serialize = "enabled")]
+    Enabled,
+    /// Enabled through `/run`, until next reboot
+    #[strum(serialize = "enabled-runtime")]
+    EnabledRuntime,
+    /// Made available through a symlink to a file outside of the search path
+    #[strum(serialize = "linked")]
+    Linked,
+    /// Linked through `/run`, until next reboot
+    #[strum(serialize = "linked-runtime")]
+    LinkedRuntime,
+    /// The name is an alias of another unit
+    #[strum(serialize = "alias")]
+    Alias,
+    /// Completely disabled, starting it fails
+    #[strum(serialize = "masked")]
+    Masked,
+    /// Masked through `/run`, until next reboot
+    #[strum(serialize = "masked-runtime")]
+    MaskedRuntime,
+    /// Not enabled, and no `[Install]` section to enable it
+    #[strum(serialize = "static")]
+    Static,
+    /// Not enabled, its `Also=` units are
+    #[strum(serialize = "indirect")]
+    Indirect,
+    /// Not enabled, but could be
+    #[strum(serialize = "disabled")]
+    #[default]
+    Disabled,
+    /// Dynamically generated by a generator
+    #[strum(serialize = "generated")]
+    Generated,
+    /// Dynamically created through the runtime API
+    #[strum(serialize = "transient")]
+    Transient,
+    /// Invalid unit file
+    #[strum(serialize = "bad")]
+    Bad,
+    /// No such unit file
+    #[strum(serialize = "not-found")]
+    NotFound,
+}
+
+impl EnablementState {
+    /// Returns `true` for states `systemctl is-enabled` reports as successful
+    pub fn is_enabled(&self) -> bool {
+        matches!(
+            self,
+            Self::Enabled
+                | Self::EnabledRuntime
+                | Self::Static
+                | Self::Alias
+                | Self::Indirect
+                | Self::Generated
+                | Self::Transient
+        )
+    }
+
+    /// Returns `true` if the unit is masked
+    pub fn is_masked(&self) -> bool {
+        matches!(self, Self::Masked | Self::MaskedRuntime)
+    }
+}
+
 /*
 /// Process
 #[derive(Clone, Debug)]
@@ -505,6 +967,10 @@ pub enum Doc {
     Man(String),
     /// Webpage URL is indicated
     Url(String),
+    /// Local file (`file:`), holds its path
+    File(String),
+    /// Info page (`info:`)
+    Info(String),
 }
 
 impl Doc {
@@ -522,26 +988,50 @@ impl Doc {
             _ => None,
         }
     }
+    /// Unwrapps self as local `File` path
+    pub fn as_file(&self) -> Option<&str> {
+        match self {
+            Doc::File(s) => Some(s),
+            _ => None,
+        }
+    }
+    /// Unwrapps self as `Info` page
+    pub fn as_info(&self) -> Option<&str> {
+        match self {
+            Doc::Info(s) => Some(s),
+            _ => None,
+        }
+    }
 }
 
 impl std::str::FromStr for Doc {
     type Err = std::io::Error;
-    /// Builds `Doc` from systemd status descriptor
+    /// Builds `Doc` from systemd status descriptor,
+    /// like `man:cron(8)` or `https://example.org:8080/doc`
     fn from_str(status: &str) -> Result<Self, Self::Err> {
-        let items: Vec<&str> = status.split(':').collect();
-        if items.len() != 2 {
+        let status = status.trim();
+        // only the scheme is split: URLs may hold other colons
+        let (scheme, content) = status.split_once(':').ok_or_else(|| {
+            std::io::Error::new(ErrorKind::InvalidData, "malformed doc descriptor")
+        })?;
+        if content.is_empty() {
             return Err(std::io::Error::new(
                 ErrorKind::InvalidData,
                 "malformed doc descriptor",
             ));
         }
-        match items[0] {
+        match scheme {
             "man" => {
-                let content: Vec<&str> = items[1].split('(').collect();
+                let content: Vec<&str> = content.split('(').collect();
                 Ok(Doc::Man(content[0].to_string()))
             },
-            "http" => Ok(Doc::Url("http:".to_owned() + items[1].trim())),
-            "https" => Ok(Doc::Url("https:".to_owned() + items[1].trim())),
+            "http" | "https" => Ok(Doc::Url(status.to_string())),
+            "file" => {
+                // `file:///path` and `file:/path` forms
+                let path = content.strip_prefix("//").unwrap_or(content);
+                Ok(Doc::File(path.to_string()))
+            },
+            "info" => Ok(Doc::Info(content.to_string())),
             _ => Err(std::io::Error::new(
                 ErrorKind::InvalidData,
                 "unknown type of doc",
@@ -566,6 +1056,8 @@ pub struct Unit {
     pub auto_start: AutoStartStatus,
     /// `true` if Self is actively running
     pub active: bool,
+    /// Detailed activation state
+    pub active_state: ActiveState,
     /// `true` if this unit is auto started by default,
     /// meaning, it should be manually disabled
     /// not to automatically start
@@ -582,9 +1074,11 @@ pub struct Unit {
     pub pid: Option<u64>,
     /// Running task(s) infos
     pub tasks: Option<u64>,
-    /// Optionnal CPU load consumption infos
+    /// Optionnal CPU load consumption infos, as `systemctl status` prints them
+    /// like `1.234s`. [Unit::resources] holds the raw counter.
     pub cpu: Option<String>,
-    /// Optionnal Memory consumption infos
+    /// Optionnal Memory consumption infos, as `systemctl status` prints them
+    /// like `1.2M (peak: 3.4M)`. [Unit::resources] holds the raw counters.
     pub memory: Option<String>,
     /// mounted partition (`What`), if this is a `mount`/`automount` unit
     pub mounted: Option<String>,
@@ -612,6 +1106,421 @@ pub struct Unit {
     /// by the service manager like any other service, and thus shows up in the output
     /// of systemctl list-units like any other unit.
     pub transient: bool,
+    /// Time of the last active state change, in UTC
+    pub state_change_timestamp: Option<SystemTime>,
+    /// Next elapse time (UTC), if this is a `timer` unit
+    pub next_trigger: Option<SystemTime>,
+    /// Slice this unit belongs to, like `system.slice`
+    pub slice: Option<String>,
+    /// Time it took to activate this unit, the last time it started
+    pub startup_time: Option<Duration>,
+    /// Resources consumed by this unit, `memory`, `cpu` and `tasks` in numbers
+    pub resources: ResourceUsage,
+    /// Why this unit is running, `None` when inactive or unknown.
+    /// See [SystemCtl::activation_source] for a more accurate answer.
+    pub activation_source: Option<ActivationSource>,
+}
+
+impl Unit {
+    /// Builds a `Unit` from the output of `systemctl status $unit`,
+    /// without invoking anything. This is handy to analyze captured outputs,
+    /// for example from support bundles or in tests.
+    /// Timestamps are only parsed when expressed in UTC (`--timestamp=utc`).
+    /// Fields that are not part of the status output (`wants`, `exec_start`..)
+    /// are left unset.
+    pub fn parse_status(status: &str) -> std::io::Result<Unit> {
+        Self::parse_status_with(status, false)
+    }
+
+    /// Builds a `Unit` from the output of `systemctl status $unit`, failing
+    /// with a [ParseError] on any unknown line or value, see [SystemCtlBuilder::strict].
+    /// Timestamps in other time zones than UTC are not errors.
+    pub fn parse_status_strict(status: &str) -> std::io::Result<Unit> {
+        Self::parse_status_with(status, true)
+    }
+
+    fn parse_status_with(status: &str, strict: bool) -> std::io::Result<Unit> {
+        let check = Strictness {
+            strict,
+            output: "status",
+        };
+        let mut u = Unit::default();
+        let mut lines = status.lines();
+        let header = lines.next().unwrap_or_default();
+        // skip the leading status bullet (●, ○, ×..), if any
+        let mut items = header
+            .split_ascii_whitespace()
+            .skip_while(|item| !item.contains('.'));
+        let name_raw = items.next().ok_or_else(|| {
+            Error::new(ErrorKind::InvalidData, "missing unit name in status header")
+        })?;
+        if let Some(delim) = items.next() {
+            if delim.trim().eq("-") {
+                // --> description string is provided
+                let items: Vec<_> = items.collect();
+                u.description = Some(itertools::join(&items, " "));
+            }
+        }
+        u.name = name_raw.to_string();
+        u.utype = Type::from_unit_name(name_raw)?;
+        let mut field = "";
+        for line in lines {
+            let line = line.trim_start();
+            if line.is_empty() {
+                // journal excerpt follows
+                break;
+            }
+            let raw = line;
+            if let Some(name) = status_field(line) {
+                field = name;
+            }
+            if let Some(line) = line.strip_prefix("Loaded: ") {
+                // Match and get rid of "Loaded: "
+                if let Some(line) = line.strip_prefix("loaded ") {
+                    u.state = State::Loaded;
+                    let line = line.trim_start_matches('(').trim_end_matches(')');
+                    let items: Vec<&str> = line.split(';').collect();
+                    u.script = items[0].trim().to_string();
+                    if items.len() > 1 {
+                        let auto_start = AutoStartStatus::from_str(items[1].trim());
+                        u.auto_start = check
+                            .value(raw, auto_start.map_err(|_| "unknown unit file state"))?
+                            .unwrap_or(AutoStartStatus::Disabled);
+                    }
+                    if items.len() > 2 {
+                        // preset is optionnal ?
+                        u.preset = items[2].trim().ends_with("enabled");
+                    }
+                } else if line.starts_with("masked") {
+                    u.state = State::Masked;
+                } else {
+                    check.reject(raw, "unknown load state")?;
+                }
+            } else if let Some(line) = line.strip_prefix("Transient: ") {
+                if line == "yes" {
+                    u.transient = true
+                }
+            } else if let Some(line) = line.strip_prefix("Active: ") {
+                // example -> Active: active (running) since Thu 2024-01-11 10:00:00 UTC; 2h ago
+                u.active = line.starts_with("active ");
+                let state = line.split_ascii_whitespace().next().unwrap_or_default();
+                let state = ActiveState::from_str(state).map_err(|_| "unknown active state");
+                u.active_state = check.value(raw, state)?.unwrap_or_default();
+                if let Some((_, since)) = line.split_once(" since ") {
+                    let since = since.split(';').next().unwrap_or_default();
+                    u.state_change_timestamp = parse_utc_timestamp(since);
+                }
+            } else if let Some(line) = line.strip_prefix("Trigger: ") {
+                // timer units -> Trigger: Fri 2024-01-12 00:00:00 UTC; 13h left
+                let next = line.split(';').next().unwrap_or_default();
+                u.next_trigger = parse_utc_timestamp(next);
+            } else if let Some(line) = line.strip_prefix("Docs: ") {
+                if let Some(doc) = check.value(raw, Doc::from_str(line))? {
+                    u.docs.get_or_insert_with(Vec::new).push(doc);
+                }
+            } else if let Some(line) = line.strip_prefix("What: ") {
+                // mountpoint infos
+                u.mounted = Some(line.to_string())
+            } else if let Some(line) = line.strip_prefix("Where: ") {
+                // mountpoint infos
+                u.mountpoint = Some(line.to_string());
+            } else if let Some(line) = line.strip_prefix("Main PID: ") {
+                // example -> Main PID: 787 (gpm)
+                if let Some((pid, proc)) = line.split_once(' ') {
+                    u.pid = Some(check.value(raw, pid.parse::<u64>())?.unwrap_or(0));
+                    u.process = Some(proc.replace(&['(', ')'][..], ""));
+                };
+            } else if let Some(line) = line.strip_prefix("Cntrl PID: ") {
+                // example -> Main PID: 787 (gpm)
+                if let Some((pid, proc)) = line.split_once(' ') {
+                    u.pid = Some(check.value(raw, pid.parse::<u64>())?.unwrap_or(0));
+                    u.process = Some(proc.replace(&['(', ')'][..], ""));
+                };
+            } else if line.starts_with("Process: ") {
+                //TODO: implement
+                //TODO: parse as a Process item
+                //let items : Vec<_> = line.split_ascii_whitespace().collect();
+                //let proc_pid = u64::from_str_radix(items[1].trim(), 10).unwrap();
+                //let cli;
+                //Process: 640 ExecStartPre=/usr/sbin/sshd -t (code=exited, status=0/SUCCESS)
+            } else if let Some(line) = line.strip_prefix("CGroup: ") {
+                //TODO: parse processes
+                //LINE: "CGroup: /system.slice/sshd.service"
+                //LINE: "└─1050 /usr/sbin/sshd -D"
+                u.slice = line
+                    .trim()
+                    .rsplit('/')
+                    .find(|item| item.ends_with(".slice"))
+                    .map(str::to_string);
+            } else if let Some(line) = line.strip_prefix("Tasks: ") {
+                // example -> Tasks: 1 (limit: 4915)
+                let tasks = line.split_ascii_whitespace().next().unwrap_or_default();
+                u.tasks = check.value(raw, tasks.parse::<u64>())?;
+                u.resources.tasks_current = u.tasks;
+            } else if let Some(line) = line.strip_prefix("Memory: ") {
+                // example -> Memory: 1.2M (peak: 3.4M)
+                // or, with systemd >= 255 -> Memory: 6.7M (peak: 8.9M swap: 0B ...)
+                u.memory = Some(line.trim().to_string());
+                let (current, details) = line.split_once(' ').unwrap_or((line, ""));
+                u.resources.memory_current = parse_bytes(current);
+                u.resources.memory_peak = details
+                    .split_once("peak: ")
+                    .and_then(|(_, peak)| peak.split_ascii_whitespace().next())
+                    .and_then(|peak| parse_bytes(peak.trim_end_matches(')')));
+            } else if let Some(line) = line.strip_prefix("CPU: ") {
+                u.cpu = Some(line.trim().to_string());
+                u.resources.cpu_usage_nsec = parse_timespan(line).map(|cpu| cpu.as_nanos() as u64);
+            } else if let Some(name) = status_field(line) {
+                if !STATUS_FIELDS_IGNORED.contains(&name) {
+                    check.reject(raw, "unknown field")?;
+                }
+            } else if field == "Docs" {
+                // handling multi line cases
+                if let Some(doc) = check.value(raw, Doc::from_str(line))? {
+                    u.docs.get_or_insert_with(Vec::new).push(doc);
+                }
+            } else if !STATUS_FIELDS_MULTILINE.contains(&field) {
+                check.reject(raw, format!("unexpected line after \"{}\"", field))?;
+            }
+        }
+        Ok(u)
+    }
+
+    /// Builds a `Unit` from the `KEY=VALUE` output of `systemctl show $unit`,
+    /// without invoking anything. Unknown properties are ignored.
+    /// Timestamps are only parsed when expressed in UTC (`--timestamp=utc`).
+    pub fn parse_show(show: &str) -> std::io::Result<Unit> {
+        Self::from_properties(show.lines().filter_map(|line| line.split_once('=')), false)
+    }
+
+    /// Builds a `Unit` from the output of `systemctl show $unit`, failing with
+    /// a [ParseError] on malformed lines and unknown values of the parsed
+    /// properties, see [SystemCtlBuilder::strict]. Unknown properties are
+    /// still ignored, as `show` prints hundreds of them.
+    pub fn parse_show_strict(show: &str) -> std::io::Result<Unit> {
+        let check = Strictness {
+            strict: true,
+            output: "show",
+        };
+        let mut properties = Vec::new();
+        for line in show.lines().filter(|line| !line.is_empty()) {
+            match line.split_once('=') {
+                Some(property) => properties.push(property),
+                None => check.reject(line, "not a KEY=VALUE property")?,
+            }
+        }
+        Self::from_properties(properties.into_iter(), true)
+    }
+
+    /// Builds a `Unit` from `systemctl show` properties
+    fn from_properties<'a>(
+        properties: impl Iterator<Item = (&'a str, &'a str)>,
+        strict: bool,
+    ) -> std::io::Result<Unit> {
+        let check = Strictness {
+            strict,
+            output: "show",
+        };
+        let mut u = Unit::default();
+        let (mut inactive_exit, mut active_enter) = (None, None);
+        let (mut triggered_by, mut required_by) = (Vec::new(), Vec::new());
+        let (mut main_pid, mut control_pid) = (None, None);
+        let properties = properties.filter(|(_, v)| !v.is_empty() && *v != "[not set]");
+        for (k, v) in properties {
+            let val = v.to_string();
+            let line = format_args!("{}={}", k, v);
+            if let Some(counter) = u.resources.counter_mut(k) {
+                *counter = check.value(line, resources::parse_counter(v))?.flatten();
+            }
+            match k {
+                "Id" => {
+                    u.utype = Type::from_unit_name(v)?;
+                    u.name = val;
+                },
+                "Description" => u.description = Some(val),
+                "LoadState" => {
+                    let state = State::from_str(v).map_err(|_| "unknown load state");
+                    if let Some(state) = check.value(line, state)? {
+                        u.state = state
+                    }
+                },
+                "UnitFileState" => {
+                    let auto_start =
+                        AutoStartStatus::from_str(v).map_err(|_| "unknown unit file state");
+                    u.auto_start = check.value(line, auto_start)?.unwrap_or_default();
+                },
+                "UnitFilePreset" => u.preset = v == "enabled",
+                "FragmentPath" => u.script = val,
+                "ActiveState" => {
+                    u.active = v == "active";
+                    let state = ActiveState::from_str(v).map_err(|_| "unknown active state");
+                    u.active_state = check.value(line, state)?.unwrap_or_default();
+                },
+                "Transient" => u.transient = v == "yes",
+                "Restart" => u.restart_policy = Some(val),
+                "KillMode" => u.kill_mode = Some(val),
+                "MainPID" => main_pid = check.value(line, v.parse::<u64>())?,
+                "ControlPID" => control_pid = check.value(line, v.parse::<u64>())?,
+                "TasksCurrent" => u.tasks = check.value(line, v.parse::<u64>())?,
+                "What" => u.mounted = Some(val),
+                "Where" => u.mountpoint = Some(val),
+                "Documentation" => {
+                    for doc in v.split_ascii_whitespace() {
+                        if let Some(doc) = check.value(line, Doc::from_str(doc))? {
+                            u.docs.get_or_insert_with(Vec::new).push(doc);
+                        }
+                    }
+                },
+                "Wants" => u.wants = Some(split_unit_list(v)),
+                "WantedBy" => u.wanted_by = Some(split_unit_list(v)),
+                "RequiredBy" => required_by = split_unit_list(v),
+                "TriggeredBy" => triggered_by = split_unit_list(v),
+                "Before" => u.before = Some(split_unit_list(v)),
+                "After" => u.after = Some(split_unit_list(v)),
+                "ExecStart" => u.exec_start = exec_command_line(v),
+                "ExecReload" => u.exec_reload = exec_command_line(v),
+                "StateChangeTimestamp" => u.state_change_timestamp = parse_utc_timestamp(v),
+                "NextElapseUSecRealtime" => u.next_trigger = parse_utc_timestamp(v),
+                "Slice" => u.slice = Some(val),
+                "InactiveExitTimestampMonotonic" => {
+                    inactive_exit = check.value(line, v.parse::<u64>())?
+                },
+                "ActiveEnterTimestampMonotonic" => {
+                    active_enter = check.value(line, v.parse::<u64>())?
+                },
+                _ => {},
+            }
+        }
+        // 0 if no such process, the control process is only a fallback
+        u.pid = [main_pid, control_pid]
+            .into_iter()
+            .flatten()
+            .find(|pid| *pid != 0);
+        // same format as `systemctl status`
+        u.memory = u
+            .resources
+            .memory_current
+            .map(|current| match u.resources.memory_peak {
+                Some(peak) => format!(
+                    "{} (peak: {})",
+                    resources::format_bytes(current),
+                    resources::format_bytes(peak)
+                ),
+                None => resources::format_bytes(current),
+            });
+        u.cpu = u.resources.cpu_time().map(timestamp::format_timespan);
+        if let (Some(exit @ 1..), Some(enter)) = (inactive_exit, active_enter) {
+            // 0 if never happened
+            if enter >= exit {
+                u.startup_time = Some(Duration::from_micros(enter - exit));
+            }
+        }
+        if activation::is_running(u.active_state) {
+            let mut pulled_in_by = u.wanted_by.clone().unwrap_or_default();
+            pulled_in_by.extend(required_by);
+            u.activation_source = Some(ActivationSource::from_dependencies(
+                &triggered_by,
+                &pulled_in_by,
+            ));
+        }
+        if u.pid.is_some() {
+            // `show` does not expose the process name, deduce it from the binary
+            u.process = u
+                .exec_start
+                .as_deref()
+                .and_then(|cmd| cmd.split_ascii_whitespace().next())
+                .and_then(|bin| bin.rsplit('/').next())
+                .map(str::to_string);
+        }
+        Ok(u)
+    }
+}
+
+/// `systemctl status` fields that are not parsed, accepted in strict mode
+const STATUS_FIELDS_IGNORED: &[&str] = &[
+    "Drop-In",
+    "TriggeredBy",
+    "Triggers",
+    "Invocation",
+    "Status",
+    "Listen",
+    "Accepted",
+    "Connected",
+    "Condition",
+    "Notice",
+    "Warning",
+    "Follows",
+    "Device",
+    "Options",
+    "Type",
+    "IP",
+    "IO",
+    "Mem peak",
+    "Swap peak",
+];
+
+/// `systemctl status` fields that may span several lines
+const STATUS_FIELDS_MULTILINE: &[&str] = &[
+    "Docs",
+    "Drop-In",
+    "CGroup",
+    "Listen",
+    "TriggeredBy",
+    "Triggers",
+];
+
+/// Field name of a `systemctl status` line, like `Main PID` in `Main PID: 787 (gpm)`
+fn status_field(line: &str) -> Option<&str> {
+    let (name, _) = line.split_once(": ")?;
+    let valid = name
+        .chars()
+        .all(|c| c.is_ascii_alphabetic() || c == ' ' || c == '-');
+    (!name.is_empty() && valid).then_some(name)
+}
+
+/// Parses `KEY=VALUE` lines, as printed by `systemctl show`
+fn parse_properties(content: &str) -> HashMap<String, String> {
+    content
+        .lines()
+        .filter_map(|line| line.split_once('='))
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect()
+}
+
+/// Parses the single word printed by `is-active`/`is-enabled`
+fn parse_state<T: FromStr>(unit: &str, result: &RunResult) -> std::io::Result<T> {
+    let state = result.stdout.trim();
+    // failures are only reported on stderr, like
+    // `Failed to get unit file state for foo.service: No such file or directory`
+    if state.is_empty() && !result.exit_status.success() {
+        let reason = result.stderr.trim();
+        if reason.contains("No such file or directory") || reason.contains("not found") {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("Unit or service \"{}\" does not exist", unit),
+            ));
+        }
+        return Err(Error::other(reason.to_string()));
+    }
+    T::from_str(state).map_err(|_| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("unknown unit state \"{}\"", state),
+        )
+    })
+}
+
+/// Splits a space separated list of units, as exposed by `systemctl show`
+fn split_unit_list(value: &str) -> Vec<String> {
+    value.split_ascii_whitespace().map(str::to_string).collect()
+}
+
+/// Extracts the command line from an `Exec*` property exposed by `systemctl show`:
+/// `{ path=/usr/sbin/cron ; argv[]=/usr/sbin/cron -f ; ignore_errors=no ; ... }`
+fn exec_command_line(value: &str) -> Option<String> {
+    let (_, argv) = value.split_once("argv[]=")?;
+    let argv = argv.split(" ;").next().unwrap_or_default();
+    Some(argv.trim().to_string())
 }
 
 #[cfg(test)]
@@ -708,9 +1617,8 @@ mod test {
         let units = ctl.list_units(None, None, None).unwrap(); // all units
         for unit in units {
             let unit = unit.as_str();
-            if unit.contains('@') {
-                // not testing this one
-                // would require @x service # identification / enumeration
+            if UnitName::parse(unit).map_or(true, |name| name.is_template()) {
+                // templates can only be created through their instances
                 continue;
             }
             let c0 = unit.chars().next().unwrap();
@@ -742,6 +1650,455 @@ mod test {
         }
     }
 
+    #[test]
+    fn test_target_args() {
+        assert!(ctl().target_args().is_empty());
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .host("root@web1".to_string())
+            .machine("db".to_string())
+            .build();
+        assert_eq!(ctl.target_args(), vec!["-H", "root@web1", "-M", "db"]);
+        let e = ctl.is_active("cron.service").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidInput);
+    }
+
+    #[test]
+    fn test_pre_spawn_hook() {
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .path("/usr/bin/env".to_string())
+            .pre_spawn(|cmd: &mut Command| {
+                cmd.env("SYSTEMCTL_HOOK", "applied");
+            })
+            .build();
+        let output = ctl.systemctl_capture(vec![]).unwrap();
+        assert!(output.stdout.contains("SYSTEMCTL_HOOK=applied"));
+    }
+
+    #[derive(Debug)]
+    struct ActiveBackend;
+
+    impl Backend for ActiveBackend {
+        fn start(&self, _: &str) -> std::io::Result<RunResult> {
+            Err(Error::other("not supported"))
+        }
+        fn stop(&self, _: &str) -> std::io::Result<RunResult> {
+            Err(Error::other("not supported"))
+        }
+        fn restart(&self, _: &str) -> std::io::Result<RunResult> {
+            Err(Error::other("not supported"))
+        }
+        fn reload(&self, _: &str) -> std::io::Result<RunResult> {
+            Err(Error::other("not supported"))
+        }
+        fn is_active(&self, _: &str) -> std::io::Result<bool> {
+            Ok(true)
+        }
+        fn show(&self, unit: &str, _: &[&str]) -> std::io::Result<HashMap<String, String>> {
+            Ok(HashMap::from([
+                ("Id".to_string(), unit.to_string()),
+                ("LoadState".to_string(), "loaded".to_string()),
+                ("ActiveState".to_string(), "active".to_string()),
+                ("MemoryCurrent".to_string(), "[not set]".to_string()),
+            ]))
+        }
+        fn list_unit_files(
+            &self,
+            _: Option<&str>,
+            _: Option<&str>,
+            _: Option<&str>,
+        ) -> std::io::Result<Vec<UnitList>> {
+            Ok(Vec::new())
+        }
+    }
+
+    #[test]
+    fn test_backend_dispatch() {
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .path("/nonexistent/systemctl".to_string())
+            .backend(Arc::new(ActiveBackend))
+            .build();
+        assert!(ctl.is_active("cron.service").unwrap());
+        assert!(ctl.start("cron.service").is_err());
+        let unit = ctl.create_unit("cron.service").unwrap();
+        assert_eq!(unit.name, "cron.service");
+        assert!(unit.active);
+        assert_eq!(unit.memory, None);
+
+        // backends only reach the local manager of their connection
+        for ctl in [
+            SystemCtl::builder()
+                .additional_args(vec!["--user".to_string()])
+                .backend(Arc::new(ActiveBackend))
+                .build(),
+            SystemCtl::builder()
+                .additional_args(vec![])
+                .host("root@web1".to_string())
+                .backend(Arc::new(ActiveBackend))
+                .build(),
+            SystemCtl::builder()
+                .additional_args(vec![])
+                .machine("debian".to_string())
+                .backend(Arc::new(ActiveBackend))
+                .build(),
+        ] {
+            let e = ctl.is_active("cron.service").unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::Unsupported);
+            let e = ctl.restart("cron.service").unwrap_err();
+            assert_eq!(e.kind(), ErrorKind::Unsupported);
+        }
+    }
+
+    /// `systemctl` of systemd < 248, rejecting `--timestamp=utc`
+    #[derive(Debug, Default)]
+    struct LegacySystemctl {
+        calls: std::sync::Mutex<Vec<Vec<String>>>,
+    }
+
+    impl SystemctlExecutor for LegacySystemctl {
+        fn execute(
+            &self,
+            command: &mut Command,
+            _: Option<Duration>,
+        ) -> std::io::Result<std::process::Output> {
+            use std::os::unix::process::ExitStatusExt;
+            let args: Vec<String> = command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect();
+            let tz = command
+                .get_envs()
+                .find(|(key, _)| *key == "TZ")
+                .and_then(|(_, value)| value)
+                .map(|value| value.to_string_lossy().into_owned())
+                .unwrap_or_else(|| "CET".to_string());
+            let legacy = !args.iter().any(|arg| arg == "--timestamp=utc");
+            self.calls.lock().unwrap().push(args);
+            let (code, stdout, stderr) = if legacy {
+                let stdout = format!(
+                    "Id=foo.service\nLoadState=loaded\nStateChangeTimestamp=Thu 2024-01-11 10:00:00 {}\n",
+                    tz
+                );
+                (0, stdout, String::new())
+            } else {
+                let stderr = "systemctl: unrecognized option '--timestamp=utc'\n";
+                (1, String::new(), stderr.to_string())
+            };
+            Ok(std::process::Output {
+                status: ExitStatus::from_raw(code << 8),
+                stdout: stdout.into_bytes(),
+                stderr: stderr.into_bytes(),
+            })
+        }
+    }
+
+    #[test]
+    fn test_legacy_timestamps() {
+        let legacy = Arc::new(LegacySystemctl::default());
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![])
+            .executor(legacy.clone())
+            .build();
+        for _ in 0..2 {
+            let properties = ctl.show("foo.service", &["StateChangeTimestamp"]).unwrap();
+            assert_eq!(
+                parse_utc_timestamp(&properties["StateChangeTimestamp"]),
+                Some(std::time::UNIX_EPOCH + Duration::from_secs(1704967200))
+            );
+        }
+        // the flag is only tried once
+        let calls = legacy.calls.lock().unwrap();
+        assert_eq!(calls.len(), 3);
+        assert_eq!(
+            calls[2],
+            vec!["show", "-p", "StateChangeTimestamp", "foo.service"]
+        );
+    }
+
+    #[test]
+    fn test_masked_unit() {
+        let mock = MockExecutor::new();
+        mock.on(
+            &["start"],
+            MockResponse::failure(
+                1,
+                "Failed to start bar.service: Unit bar.service is masked.\n",
+            ),
+        );
+        let e = mock.ctl().start("bar.service").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::PermissionDenied);
+        assert_eq!(
+            e.to_string(),
+            "Failed to start bar.service: Unit bar.service is masked."
+        );
+    }
+
+    #[test]
+    fn test_parse_state() {
+        // nope.service does not exist
+        let mock = MockExecutor::new();
+        mock.on(
+            &["is-active", "foo.service"],
+            MockResponse::success("activating\n"),
+        )
+        .on(
+            &["is-enabled", "foo.service"],
+            MockResponse::success("masked-runtime\n"),
+        )
+        .on(
+            &["nope.service"],
+            MockResponse::failure(
+                1,
+                "Failed to get unit file state for nope.service: No such file or directory\n",
+            ),
+        )
+        .on(
+            &["is-active", "bar.service"],
+            MockResponse::success("unknown\n"),
+        )
+        .on(
+            &["is-enabled", "bar.service"],
+            MockResponse::failure(1, "Access denied\n"),
+        );
+        let ctl = mock.ctl();
+        assert_eq!(
+            ctl.active_state("foo.service").unwrap(),
+            ActiveState::Activating
+        );
+        assert!(!ActiveState::Failed.is_active());
+        assert!(ActiveState::Reloading.is_active());
+        assert_eq!(
+            ctl.enablement_state("foo.service").unwrap(),
+            EnablementState::MaskedRuntime
+        );
+        assert!(EnablementState::MaskedRuntime.is_masked());
+        assert!(EnablementState::Static.is_enabled());
+        assert!(!EnablementState::Linked.is_enabled());
+        let e = ctl.active_state("bar.service").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::InvalidData);
+        let e = ctl.enablement_state("nope.service").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::NotFound);
+        let e = ctl.enablement_state("bar.service").unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::Other);
+        assert_eq!(e.to_string(), "Access denied");
+    }
+
+    #[test]
+    fn test_doc_from_str() {
+        let docs = [
+            ("man:systemd.service(5)", Doc::Man("systemd.service".into())),
+            (
+                "https://www.freedesktop.org/software/systemd/man/systemd-journald.html",
+                Doc::Url(
+                    "https://www.freedesktop.org/software/systemd/man/systemd-journald.html".into(),
+                ),
+            ),
+            (
+                "http://localhost:8080/docs",
+                Doc::Url("http://localhost:8080/docs".into()),
+            ),
+            (
+                "file:/usr/share/doc/dbus/README",
+                Doc::File("/usr/share/doc/dbus/README".into()),
+            ),
+            (
+                "file:///usr/share/doc/tor/README",
+                Doc::File("/usr/share/doc/tor/README".into()),
+            ),
+            ("info:coreutils", Doc::Info("coreutils".into())),
+        ];
+        for (value, expected) in docs {
+            assert_eq!(Doc::from_str(value).unwrap(), expected);
+        }
+        assert_eq!(
+            Doc::from_str("info:coreutils").unwrap().as_info(),
+            Some("coreutils")
+        );
+        assert!(Doc::from_str("ftp://example.org").is_err());
+        assert!(Doc::from_str("man:").is_err());
+        assert!(Doc::from_str("nothing").is_err());
+    }
+
+    #[test]
+    fn test_parse_status() {
+        let status = "\
+● cron.service - Regular background program processing daemon
+     Loaded: loaded (/lib/systemd/system/cron.service; enabled; vendor preset: enabled)
+     Active: active (running) since Thu 2024-01-11 10:00:00 UTC; 2h ago
+       Docs: man:cron(8)
+   Main PID: 787 (cron)
+      Tasks: 1 (limit: 4915)
+     Memory: 1.2M
+        CPU: 1.234s
+     CGroup: /system.slice/cron.service
+             └─787 /usr/sbin/cron -f";
+        let u = Unit::parse_status(status).unwrap();
+        assert_eq!(u.name, "cron.service");
+        assert_eq!(u.utype, Type::Service);
+        assert_eq!(
+            u.description.as_deref(),
+            Some("Regular background program processing daemon")
+        );
+        assert_eq!(u.state, State::Loaded);
+        assert_eq!(u.script, "/lib/systemd/system/cron.service");
+        assert_eq!(u.auto_start, AutoStartStatus::Enabled);
+        assert!(u.preset);
+        assert!(u.active);
+        assert_eq!(u.active_state, ActiveState::Active);
+        assert_eq!(u.pid, Some(787));
+        assert_eq!(u.process.as_deref(), Some("cron"));
+        assert_eq!(u.memory.as_deref(), Some("1.2M"));
+        assert_eq!(u.tasks, Some(1));
+        assert_eq!(u.resources.memory_current, Some(1258291));
+        assert_eq!(u.resources.cpu_usage_nsec, Some(1_234_000_000));
+        assert_eq!(u.resources.tasks_current, Some(1));
+        assert_eq!(u.slice.as_deref(), Some("system.slice"));
+        assert_eq!(u.docs, Some(vec![Doc::Man("cron".into())]));
+        assert_eq!(
+            u.state_change_timestamp,
+            parse_utc_timestamp("2024-01-11 10:00:00 UTC")
+        );
+
+        let masked = "○ foo.timer\n     Loaded: masked (Reason: Unit foo.timer is masked.)";
+        let u = Unit::parse_status(masked).unwrap();
+        assert_eq!(u.utype, Type::Timer);
+        assert_eq!(u.state, State::Masked);
+        assert!(!u.active);
+
+        let memory = "● foo.service\n     Memory: 6.7M (peak: 8.9M swap: 0B swap peak: 0B)";
+        let u = Unit::parse_status(memory).unwrap();
+        assert_eq!(u.resources.memory_current, Some(7025459));
+        assert_eq!(u.resources.memory_peak, Some(9332326));
+
+        assert!(Unit::parse_status("").is_err());
+    }
+
+    #[test]
+    fn test_parse_show() {
+        let show = "\
+Id=cron.service
+Description=Regular background program processing daemon
+LoadState=loaded
+ActiveState=active
+UnitFileState=enabled
+UnitFilePreset=enabled
+FragmentPath=/lib/systemd/system/cron.service
+MainPID=787
+ControlPID=790
+ExecMainPID=787
+TasksCurrent=1
+MemoryCurrent=1261568
+CPUUsageNSec=[not set]
+Wants=system.slice
+After=network.target basic.target
+ExecStart={ path=/usr/sbin/cron ; argv[]=/usr/sbin/cron -f $EXTRA_OPTS ; ignore_errors=no ; start_time=[n/a] ; stop_time=[n/a] ; pid=0 ; code=(null) ; status=0/0 }
+Documentation=man:cron(8) https://example.org/cron
+StateChangeTimestamp=Thu 2024-01-11 10:00:00 UTC
+Slice=system.slice
+InactiveExitTimestampMonotonic=5000000
+ActiveEnterTimestampMonotonic=5250000";
+        let u = Unit::parse_show(show).unwrap();
+        assert_eq!(u.name, "cron.service");
+        assert_eq!(u.utype, Type::Service);
+        assert_eq!(u.state, State::Loaded);
+        assert_eq!(u.auto_start, AutoStartStatus::Enabled);
+        assert!(u.active);
+        assert_eq!(u.active_state, ActiveState::Active);
+        assert!(u.preset);
+        assert_eq!(u.pid, Some(787));
+        assert_eq!(u.tasks, Some(1));
+        assert_eq!(u.memory.as_deref(), Some("1.2M"));
+        assert_eq!(u.resources.memory_current, Some(1261568));
+        assert_eq!(u.resources.tasks_current, Some(1));
+        assert_eq!(u.resources.cpu_usage_nsec, None);
+        assert_eq!(u.cpu, None);
+        assert_eq!(u.wants, Some(vec!["system.slice".to_string()]));
+        assert_eq!(
+            u.after,
+            Some(vec![
+                "network.target".to_string(),
+                "basic.target".to_string()
+            ])
+        );
+        assert_eq!(
+            u.exec_start.as_deref(),
+            Some("/usr/sbin/cron -f $EXTRA_OPTS")
+        );
+        assert_eq!(
+            u.docs,
+            Some(vec![
+                Doc::Man("cron".into()),
+                Doc::Url("https://example.org/cron".into())
+            ])
+        );
+        assert!(u.state_change_timestamp.is_some());
+        assert_eq!(u.slice.as_deref(), Some("system.slice"));
+        assert_eq!(u.startup_time, Some(Duration::from_millis(250)));
+        assert_eq!(u.activation_source, Some(ActivationSource::Manual));
+
+        // control process while the main one is not running yet
+        let show = "Id=foo.service
+MainPID=0
+ControlPID=900
+CPUUsageNSec=1234000000
+MemoryCurrent=1261568
+MemoryPeak=3565159";
+        let u = Unit::parse_show(show).unwrap();
+        assert_eq!(u.pid, Some(900));
+        assert_eq!(u.cpu.as_deref(), Some("1.234s"));
+        assert_eq!(u.memory.as_deref(), Some("1.2M (peak: 3.4M)"));
+    }
+
+    #[test]
+    fn test_parse_strict() {
+        let status = "\
+● cron.service - Regular background program processing daemon
+     Loaded: loaded (/lib/systemd/system/cron.service; enabled; vendor preset: enabled)
+    Drop-In: /etc/systemd/system/cron.service.d
+             └─override.conf
+     Active: active (running) since Thu 2024-01-11 10:00:00 UTC; 2h ago
+       Docs: man:cron(8)
+             https://example.org/cron
+   Main PID: 787 (cron)
+      Tasks: 1 (limit: 4915)
+     CGroup: /system.slice/cron.service
+             └─787 /usr/sbin/cron -f
+
+Jan 11 10:00:00 host cron[787]: (CRON) INFO (Running @reboot jobs)";
+        let u = Unit::parse_status_strict(status).unwrap();
+        assert_eq!(u, Unit::parse_status(status).unwrap());
+        assert_eq!(u.docs.as_ref().map(Vec::len), Some(2));
+
+        let unknown = "● foo.service\n     Loaded: loaded (/foo.service; enabled)\n    Foo: bar";
+        assert!(Unit::parse_status(unknown).is_ok());
+        let err = Unit::parse_status_strict(unknown).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::InvalidData);
+        let details = err.get_ref().unwrap().downcast_ref::<ParseError>();
+        assert_eq!(
+            details,
+            Some(&ParseError {
+                output: "status".into(),
+                line: "Foo: bar".into(),
+                reason: "unknown field".into(),
+            })
+        );
+        let not_found =
+            "○ foo.service\n     Loaded: not-found (Reason: Unit foo.service not found.)";
+        assert!(Unit::parse_status_strict(not_found).is_err());
+
+        let show = "Id=foo.service\nActiveState=active\nTasksCurrent=1";
+        assert_eq!(
+            Unit::parse_show_strict(show).unwrap(),
+            Unit::parse_show(show).unwrap()
+        );
+        assert!(Unit::parse_show_strict("Id=foo.service\ngarbage").is_err());
+        let bad_state = "Id=foo.service\nActiveState=exploded";
+        assert!(Unit::parse_show(bad_state).is_ok());
+        assert!(Unit::parse_show_strict(bad_state).is_err());
+    }
+
     #[cfg(feature = "serde")]
     #[test]
     fn test_serde_for_unit() {
@@ -0,0 +1,195 @@
+//! Unit file lifecycle: mask, preset, link, revert..
+use crate::{RunResult, SystemCtl};
+use bon::Builder;
+use std::path::Path;
+use strum_macros::EnumString;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Options of the unit file commands. Not all commands
+/// honor every option, see each command.
+///
+/// ```no_run
+/// use systemctl::{SystemCtl, UnitFileOptions};
+/// let ctl = SystemCtl::default();
+/// let options = UnitFileOptions::builder().now(true).runtime(true).build();
+/// ctl.mask("nginx.service", &options).unwrap();
+/// ```
+#[derive(Builder, Default, Copy, Clone, Debug, PartialEq, Eq)]
+pub struct UnitFileOptions {
+    /// Also starts or stops the units (`--now`)
+    #[builder(default)]
+    pub now: bool,
+    /// Overwrites conflicting symlinks (`--force`)
+    #[builder(default)]
+    pub force: bool,
+    /// Applies the change until next reboot only, in `/run` (`--runtime`)
+    #[builder(default)]
+    pub runtime: bool,
+}
+
+impl UnitFileOptions {
+    /// Converts self to systemctl arguments
+    fn to_args(self) -> Vec<&'static str> {
+        let mut args = Vec::new();
+        if self.now {
+            args.push("--now");
+        }
+        if self.force {
+            args.push("--force");
+        }
+        if self.runtime {
+            args.push("--runtime");
+        }
+        args
+    }
+}
+
+/// Changes applied by [SystemCtl::preset] (`--preset-mode`)
+#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PresetMode {
+    /// Enables and disables units, following the preset policy
+    #[strum(serialize = "full")]
+    #[default]
+    Full,
+    /// Only enables units
+    #[strum(serialize = "enable-only")]
+    EnableOnly,
+    /// Only disables units
+    #[strum(serialize = "disable-only")]
+    DisableOnly,
+}
+
+impl PresetMode {
+    fn as_arg(&self) -> &'static str {
+        match self {
+            Self::Full => "--preset-mode=full",
+            Self::EnableOnly => "--preset-mode=enable-only",
+            Self::DisableOnly => "--preset-mode=disable-only",
+        }
+    }
+}
+
+impl SystemCtl {
+    /// Masks given `unit`, making it impossible to start (`systemctl mask`).
+    /// Honors `now` (stops the unit), `force` and `runtime`.
+    pub fn mask(&self, unit: &str, options: &UnitFileOptions) -> std::io::Result<RunResult> {
+        self.unit_file_command("mask", options.to_args(), unit)
+    }
+
+    /// Unmasks given `unit` (`systemctl unmask`). Honors `runtime`.
+    pub fn unmask(&self, unit: &str, options: &UnitFileOptions) -> std::io::Result<RunResult> {
+        let options = UnitFileOptions {
+            runtime: options.runtime,
+            ..Default::default()
+        };
+        self.unit_file_command("unmask", options.to_args(), unit)
+    }
+
+    /// Enables or disables given `unit` following the preset policy
+    /// (`systemctl preset`). Honors `force` and `runtime`.
+    pub fn preset(
+        &self,
+        unit: &str,
+        mode: PresetMode,
+        options: &UnitFileOptions,
+    ) -> std::io::Result<RunResult> {
+        let options = UnitFileOptions {
+            now: false,
+            ..*options
+        };
+        let mut args = options.to_args();
+        args.push(mode.as_arg());
+        self.unit_file_command("preset", args, unit)
+    }
+
+    /// Enables or disables all units following the preset policy
+    /// (`systemctl preset-all`). Honors `force` and `runtime`.
+    pub fn preset_all(
+        &self,
+        mode: PresetMode,
+        options: &UnitFileOptions,
+    ) -> std::io::Result<RunResult> {
+        let options = UnitFileOptions {
+            now: false,
+            ..*options
+        };
+        let mut args = vec!["preset-all"];
+        args.extend(options.to_args());
+        args.push(mode.as_arg());
+        self.systemctl_capture(args)
+    }
+
+    /// Reverts given `unit` to its vendor version, removing drop-ins
+    /// and overriding unit files, and unmasking it (`systemctl revert`)
+    pub fn revert(&self, unit: &str) -> std::io::Result<RunResult> {
+        self.systemctl_capture(["revert", unit])
+    }
+
+    /// Makes the unit file at given `path`, outside of the unit search path,
+    /// available (`systemctl link`). Honors `force` and `runtime`.
+    pub fn link(
+        &self,
+        path: impl AsRef<Path>,
+        options: &UnitFileOptions,
+    ) -> std::io::Result<RunResult> {
+        let path = path.as_ref();
+        if !path.is_absolute() {
+            return Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                format!("unit file path \"{}\" is not absolute", path.display()),
+            ));
+        }
+        let options = UnitFileOptions {
+            now: false,
+            ..*options
+        };
+        self.unit_file_command("link", options.to_args(), &path.to_string_lossy())
+    }
+
+    /// Disables then enables given `unit`, resetting its symlinks to the
+    /// `[Install]` section (`systemctl reenable`). Honors all options.
+    pub fn reenable(&self, unit: &str, options: &UnitFileOptions) -> std::io::Result<RunResult> {
+        self.unit_file_command("reenable", options.to_args(), unit)
+    }
+
+    /// Returns `true` if given `unit` is enabled, see [crate::EnablementState::is_enabled]
+    pub fn is_enabled(&self, unit: &str) -> std::io::Result<bool> {
+        Ok(self.enablement_state(unit)?.is_enabled())
+    }
+
+    /// Invokes `systemctl $verb $options $unit`
+    fn unit_file_command(
+        &self,
+        verb: &str,
+        options: Vec<&str>,
+        unit: &str,
+    ) -> std::io::Result<RunResult> {
+        let mut args = vec![verb];
+        args.extend(options);
+        args.push(unit);
+        self.systemctl_capture(args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_unit_file_options() {
+        assert!(UnitFileOptions::default().to_args().is_empty());
+        let options = UnitFileOptions::builder()
+            .now(true)
+            .force(true)
+            .runtime(true)
+            .build();
+        assert_eq!(options.to_args(), vec!["--now", "--force", "--runtime"]);
+        assert_eq!(PresetMode::default().as_arg(), "--preset-mode=full");
+        assert!(SystemCtl::default()
+            .link("relative/foo.service", &options)
+            .is_err());
+    }
+}
@@ -0,0 +1,125 @@
+//! Container and virtual machine discovery through `machinectl`
+use crate::SystemCtl;
+use std::io::Error;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+const MACHINECTL_PATH: &str = "/usr/bin/machinectl";
+
+/// A machine registered with `systemd-machined`, as listed by `machinectl list`.
+/// Its name can be used as [SystemCtl] `machine` target.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Machine {
+    /// Machine name
+    pub name: String,
+    /// Machine class: `container` or `vm`
+    pub class: String,
+    /// Service that registered the machine, like `systemd-nspawn`
+    pub service: String,
+    /// Operating system identifier, if known
+    pub os: Option<String>,
+    /// Operating system version, if known
+    pub version: Option<String>,
+    /// IP addresses of the machine
+    pub addresses: Vec<String>,
+}
+
+impl Machine {
+    /// Parses the output of `machinectl list --no-legend`.
+    /// Missing values (`-`) are reported as `None`.
+    pub fn parse_list(content: &str) -> Vec<Machine> {
+        let mut machines: Vec<Machine> = Vec::new();
+        for line in content.lines() {
+            if line.trim().is_empty() {
+                continue;
+            }
+            if line.starts_with(char::is_whitespace) {
+                // continuation line: additional address of previous machine
+                if let Some(machine) = machines.last_mut() {
+                    machine.addresses.push(line.trim().to_string());
+                }
+                continue;
+            }
+            let mut items = line.split_ascii_whitespace();
+            let mut next = || items.next().filter(|item| *item != "-").map(str::to_string);
+            let name = next().unwrap_or_default();
+            let class = next().unwrap_or_default();
+            let service = next().unwrap_or_default();
+            let os = next();
+            let version = next();
+            let addresses = next()
+                .into_iter()
+                .map(|a| a.trim_end_matches('…').to_string());
+            machines.push(Machine {
+                name,
+                class,
+                service,
+                os,
+                version,
+                addresses: addresses.collect(),
+            });
+        }
+        machines
+    }
+}
+
+impl SystemCtl {
+    /// Lists the containers and virtual machines registered on the
+    /// targeted host (`machinectl list`). Their names can be used
+    /// as `machine` target of a new [SystemCtl].
+    pub fn list_machines(&self) -> std::io::Result<Vec<Machine>> {
+        let mut command = std::process::Command::new(MACHINECTL_PATH);
+        if let Some(host) = &self.host {
+            command.args(["-H", host]);
+        }
+        command.args(["list", "--no-legend", "--no-pager", "--full"]);
+        self.customize(&mut command);
+        let output = self.execute(command, self.command_timeout)?;
+        if !output.status.success() {
+            return Err(Error::other(format!(
+                "machinectl failed: {}",
+                String::from_utf8_lossy(&output.stderr).trim()
+            )));
+        }
+        Ok(Machine::parse_list(&String::from_utf8_lossy(
+            &output.stdout,
+        )))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_machine_list() {
+        let content = "\
+fedora-39 container systemd-nspawn fedora 39      10.0.0.2
+                                                  fe80::1
+debian    container systemd-nspawn debian 12      -
+win11     vm        libvirt-qemu   -      -       -";
+        let machines = Machine::parse_list(content);
+        assert_eq!(machines.len(), 3);
+        assert_eq!(
+            machines[0],
+            Machine {
+                name: "fedora-39".into(),
+                class: "container".into(),
+                service: "systemd-nspawn".into(),
+                os: Some("fedora".into()),
+                version: Some("39".into()),
+                addresses: vec!["10.0.0.2".into(), "fe80::1".into()],
+            }
+        );
+        assert!(machines[1].addresses.is_empty());
+        assert_eq!(machines[2].class, "vm");
+        assert_eq!(machines[2].os, None);
+
+        // older machinectl versions only report 3 columns
+        let machines = Machine::parse_list("box container systemd-nspawn");
+        assert_eq!(machines[0].service, "systemd-nspawn");
+        assert_eq!(machines[0].version, None);
+    }
+}
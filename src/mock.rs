@@ -0,0 +1,235 @@
+//! Scripted command execution, to test code built on this crate without systemd
+use crate::SystemctlExecutor;
+use std::io::{Error, ErrorKind};
+use std::os::unix::process::ExitStatusExt;
+use std::process::{Command, ExitStatus, Output};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// [SystemctlExecutor] answering scripted responses and recording
+/// the invoked commands. Clones share their responses and records,
+/// so a clone can be handed to [SystemCtl](crate::SystemCtl) while
+/// the original is kept for assertions.
+///
+/// ```
+/// use std::sync::Arc;
+/// use systemctl::{MockExecutor, MockResponse, SystemCtl};
+///
+/// let mock = MockExecutor::new();
+/// mock.on(&["is-active", "nginx.service"], MockResponse::success("active\n"))
+///     .on(&["restart", "nginx.service"], MockResponse::success(""));
+/// let ctl = SystemCtl::builder()
+///     .additional_args(vec![])
+///     .executor(Arc::new(mock.clone()))
+///     .build();
+///
+/// // code under test
+/// if ctl.is_active("nginx.service").unwrap() {
+///     ctl.restart("nginx.service").unwrap();
+/// }
+/// assert!(mock.was_called(&["restart", "nginx.service"]));
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct MockExecutor {
+    state: Arc<Mutex<MockState>>,
+}
+
+#[derive(Debug, Default)]
+struct MockState {
+    rules: Vec<(Vec<String>, MockResponse)>,
+    calls: Vec<Invocation>,
+}
+
+/// Scripted outcome of a command run by a [MockExecutor]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct MockResponse {
+    /// Exit code of the command
+    pub exit_code: i32,
+    /// Standard output of the command
+    pub stdout: String,
+    /// Standard error of the command
+    pub stderr: String,
+    /// Fails with a [std::io::ErrorKind::TimedOut] error instead,
+    /// as a command exceeding its timeout would
+    pub hang: bool,
+}
+
+impl MockResponse {
+    /// Successful command printing `stdout`
+    pub fn success(stdout: impl Into<String>) -> Self {
+        Self {
+            stdout: stdout.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Command failing with given `exit_code` and `stderr` message
+    pub fn failure(exit_code: i32, stderr: impl Into<String>) -> Self {
+        Self {
+            exit_code,
+            stderr: stderr.into(),
+            ..Default::default()
+        }
+    }
+
+    /// Command never completing
+    pub fn hang() -> Self {
+        Self {
+            hang: true,
+            ..Default::default()
+        }
+    }
+}
+
+/// A command run by a [MockExecutor]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Invocation {
+    /// Invoked program, like `/usr/bin/systemctl`
+    pub program: String,
+    /// Arguments, global ones like `--user` included
+    pub args: Vec<String>,
+}
+
+impl Invocation {
+    /// Returns `true` if all `pattern` items are arguments of
+    /// this invocation, in the same order but not necessarily
+    /// contiguous: `["show", "cron.service"]` matches
+    /// `show --timestamp=utc -p ActiveState cron.service`.
+    pub fn matches(&self, pattern: &[&str]) -> bool {
+        let mut args = self.args.iter();
+        pattern
+            .iter()
+            .all(|expected| args.any(|arg| arg == expected))
+    }
+}
+
+impl MockExecutor {
+    /// Creates an executor without responses
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Answers the commands matching `pattern` (see [Invocation::matches])
+    /// with `response`. When several patterns match, the most recently
+    /// added one wins. Commands matching no pattern fail with a
+    /// [std::io::ErrorKind::NotFound] error.
+    pub fn on(&self, pattern: &[&str], response: MockResponse) -> &Self {
+        let pattern = pattern.iter().map(|arg| arg.to_string()).collect();
+        self.state.lock().unwrap().rules.push((pattern, response));
+        self
+    }
+
+    /// Returns the commands run so far, oldest first
+    pub fn calls(&self) -> Vec<Invocation> {
+        self.state.lock().unwrap().calls.clone()
+    }
+
+    /// Returns `true` if a command matching `pattern` was run
+    pub fn was_called(&self, pattern: &[&str]) -> bool {
+        let state = self.state.lock().unwrap();
+        state.calls.iter().any(|call| call.matches(pattern))
+    }
+}
+
+impl SystemctlExecutor for MockExecutor {
+    fn execute(&self, command: &mut Command, timeout: Option<Duration>) -> std::io::Result<Output> {
+        let invocation = Invocation {
+            program: command.get_program().to_string_lossy().into_owned(),
+            args: command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().into_owned())
+                .collect(),
+        };
+        let mut state = self.state.lock().unwrap();
+        state.calls.push(invocation.clone());
+        let response = state
+            .rules
+            .iter()
+            .rev()
+            .find(|(pattern, _)| {
+                let pattern: Vec<&str> = pattern.iter().map(String::as_str).collect();
+                invocation.matches(&pattern)
+            })
+            .map(|(_, response)| response.clone())
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorKind::NotFound,
+                    format!(
+                        "no mocked response for `{} {}`",
+                        invocation.program,
+                        invocation.args.join(" ")
+                    ),
+                )
+            })?;
+        if response.hang {
+            return Err(Error::new(
+                ErrorKind::TimedOut,
+                crate::CommandTimedOut {
+                    timeout: timeout.unwrap_or(Duration::MAX),
+                    stdout: String::new(),
+                    stderr: String::new(),
+                },
+            ));
+        }
+        Ok(Output {
+            status: ExitStatus::from_raw(response.exit_code << 8),
+            stdout: response.stdout.into_bytes(),
+            stderr: response.stderr.into_bytes(),
+        })
+    }
+}
+
+#[cfg(test)]
+impl MockExecutor {
+    /// [SystemCtl](crate::SystemCtl) running its commands with a clone of self
+    pub(crate) fn ctl(&self) -> crate::SystemCtl {
+        crate::SystemCtl::builder()
+            .additional_args(vec![])
+            .executor(Arc::new(self.clone()))
+            .build()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{SystemCtl, Unit};
+
+    #[test]
+    fn test_mock_executor() {
+        let mock = MockExecutor::new();
+        mock.on(
+            &["show", "cron.service"],
+            MockResponse::success("Id=cron.service\nLoadState=loaded\nActiveState=failed\n"),
+        )
+        .on(&["cat"], MockResponse::failure(4, "Access denied"))
+        .on(&["start"], MockResponse::hang());
+        let ctl = SystemCtl::builder()
+            .additional_args(vec!["--user".to_string()])
+            .path("/nonexistent/systemctl".to_string())
+            .executor(Arc::new(mock.clone()))
+            .build();
+
+        let unit: Unit = ctl.create_unit("cron.service").unwrap();
+        assert!(!unit.active);
+        let error = ctl.cat("cron.service").unwrap_err();
+        assert_eq!(error.kind(), ErrorKind::PermissionDenied);
+        let lines: Vec<_> = ctl.cat_stream("cron.service").unwrap().collect();
+        assert_eq!(lines.len(), 1);
+        assert!(lines[0].is_err());
+        let outcome = ctl
+            .start_and_wait("cron.service", Duration::from_secs(1))
+            .unwrap();
+        assert!(matches!(outcome, crate::StartOutcome::TimedOut { .. }));
+        assert_eq!(
+            ctl.stop("cron.service").unwrap_err().kind(),
+            ErrorKind::NotFound
+        );
+
+        let calls = mock.calls();
+        assert_eq!(calls[0].program, "/nonexistent/systemctl");
+        assert_eq!(calls[0].args[0], "--user");
+        assert!(mock.was_called(&["start", "cron.service"]));
+        assert!(!mock.was_called(&["restart"]));
+    }
+}
@@ -0,0 +1,80 @@
+//! Strict parsing of `systemctl` outputs
+use std::fmt::Display;
+use std::io::{Error, ErrorKind};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Content a strict parser did not recognize, see
+/// [SystemCtlBuilder::strict](crate::SystemCtlBuilder::strict).
+/// Carried by [std::io::Error]s of kind [ErrorKind::InvalidData]:
+///
+/// ```
+/// use systemctl::{ParseError, Unit};
+/// let err = Unit::parse_show_strict("Id=cron.service\nLoadState=bad-setting").unwrap_err();
+/// let details = err.get_ref().and_then(|e| e.downcast_ref::<ParseError>()).unwrap();
+/// assert_eq!(details.line, "LoadState=bad-setting");
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ParseError {
+    /// Parsed output, like `status` or `show`
+    pub output: String,
+    /// Offending line
+    pub line: String,
+    /// Why the line was rejected
+    pub reason: String,
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "unrecognized `{}` output: {}: \"{}\"",
+            self.output, self.reason, self.line
+        )
+    }
+}
+
+impl std::error::Error for ParseError {}
+
+impl From<ParseError> for Error {
+    fn from(error: ParseError) -> Self {
+        Error::new(ErrorKind::InvalidData, error)
+    }
+}
+
+/// Tolerates unrecognized content, unless strict
+#[derive(Copy, Clone, Debug)]
+pub(crate) struct Strictness {
+    pub(crate) strict: bool,
+    pub(crate) output: &'static str,
+}
+
+impl Strictness {
+    /// Fails with a [ParseError] in strict mode, does nothing otherwise
+    pub(crate) fn reject(&self, line: impl Display, reason: impl Display) -> std::io::Result<()> {
+        if self.strict {
+            return Err(ParseError {
+                output: self.output.to_string(),
+                line: line.to_string(),
+                reason: reason.to_string(),
+            }
+            .into());
+        }
+        Ok(())
+    }
+
+    /// Returns the successfully `parsed` value of given `line`,
+    /// `None` on failure unless strict
+    pub(crate) fn value<T, E: Display>(
+        &self,
+        line: impl Display,
+        parsed: Result<T, E>,
+    ) -> std::io::Result<Option<T>> {
+        match parsed {
+            Ok(value) => Ok(Some(value)),
+            Err(e) => self.reject(line, e).map(|_| None),
+        }
+    }
+}
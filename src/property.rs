@@ -0,0 +1,162 @@
+//! Runtime resource control (`systemctl set-property`)
+use crate::{RunResult, SystemCtl};
+use std::collections::HashMap;
+use std::io::{Error, ErrorKind};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Unit property adjustable with [SystemCtl::set_property]
+#[derive(Clone, PartialEq, Eq, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Property {
+    /// CPU time quota, like `20%` (`CPUQuota`)
+    CpuQuota,
+    /// Relative CPU share, 1 to 10000 (`CPUWeight`)
+    CpuWeight,
+    /// CPUs the unit may run on, like `0-3` (`AllowedCPUs`)
+    AllowedCpus,
+    /// Hard memory limit, like `512M` or `infinity` (`MemoryMax`)
+    MemoryMax,
+    /// Memory throttling limit (`MemoryHigh`)
+    MemoryHigh,
+    /// Protected amount of memory (`MemoryLow`)
+    MemoryLow,
+    /// Hard swap limit (`MemorySwapMax`)
+    MemorySwapMax,
+    /// Maximal number of tasks, like `512` or `20%` (`TasksMax`)
+    TasksMax,
+    /// Relative IO share, 1 to 10000 (`IOWeight`)
+    IoWeight,
+    /// Any other property, by name
+    Raw(String),
+}
+
+impl Property {
+    /// Property name, as expected by `systemctl set-property`
+    pub fn name(&self) -> &str {
+        match self {
+            Self::CpuQuota => "CPUQuota",
+            Self::CpuWeight => "CPUWeight",
+            Self::AllowedCpus => "AllowedCPUs",
+            Self::MemoryMax => "MemoryMax",
+            Self::MemoryHigh => "MemoryHigh",
+            Self::MemoryLow => "MemoryLow",
+            Self::MemorySwapMax => "MemorySwapMax",
+            Self::TasksMax => "TasksMax",
+            Self::IoWeight => "IOWeight",
+            Self::Raw(name) => name,
+        }
+    }
+
+    /// Property name, as printed by `systemctl show`. Differs from
+    /// [Property::name] for `CPUQuota`, shown as `CPUQuotaPerSecUSec`.
+    pub fn show_name(&self) -> &str {
+        match self {
+            Self::CpuQuota => "CPUQuotaPerSecUSec",
+            property => property.name(),
+        }
+    }
+}
+
+impl SystemCtl {
+    /// Sets given `properties` of a running `unit` (`systemctl set-property`).
+    /// Changes persist across reboots unless `runtime` is set.
+    ///
+    /// ```no_run
+    /// use systemctl::{Property, SystemCtl};
+    /// let ctl = SystemCtl::default();
+    /// ctl.set_property(
+    ///     "nginx.service",
+    ///     &[(Property::MemoryMax, "512M"), (Property::CpuQuota, "50%")],
+    ///     true,
+    /// )
+    /// .unwrap();
+    /// ```
+    pub fn set_property(
+        &self,
+        unit: impl AsRef<str>,
+        properties: &[(Property, &str)],
+        runtime: bool,
+    ) -> std::io::Result<RunResult> {
+        if properties.is_empty() {
+            return Err(Error::new(ErrorKind::InvalidInput, "no property to set"));
+        }
+        let assignments: Vec<String> = properties
+            .iter()
+            .map(|(property, value)| format!("{}={}", property.name(), value))
+            .collect();
+        let mut args = vec!["set-property"];
+        if runtime {
+            args.push("--runtime");
+        }
+        args.push(unit.as_ref());
+        args.extend(assignments.iter().map(String::as_str));
+        self.systemctl_capture(args)
+    }
+
+    /// Returns the current values of given `properties` of `unit`,
+    /// formatted like `systemctl show` does (`MemoryMax` is shown in bytes,
+    /// `CPUQuota` as a time span per second..). Unknown properties are omitted.
+    pub fn get_properties(
+        &self,
+        unit: impl AsRef<str>,
+        properties: &[Property],
+    ) -> std::io::Result<HashMap<Property, String>> {
+        let names: Vec<&str> = properties.iter().map(Property::show_name).collect();
+        let mut values = self.show(unit.as_ref(), &names)?;
+        Ok(properties
+            .iter()
+            .filter_map(|property| {
+                let value = values.remove(property.show_name())?;
+                Some((property.clone(), value))
+            })
+            .collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_set_property() {
+        let mock = MockExecutor::new();
+        mock.on(&["set-property"], MockResponse::success("")).on(
+            &["show"],
+            MockResponse::success("CPUQuotaPerSecUSec=500ms\nMemoryMax=536870912\n"),
+        );
+        let ctl = mock.ctl();
+        ctl.set_property(
+            "foo.service",
+            &[
+                (Property::MemoryMax, "512M"),
+                (Property::Raw("ManagedOOMSwap".into()), "kill"),
+            ],
+            true,
+        )
+        .unwrap();
+        assert_eq!(
+            mock.calls()[0].args,
+            [
+                "set-property",
+                "--runtime",
+                "foo.service",
+                "MemoryMax=512M",
+                "ManagedOOMSwap=kill"
+            ]
+        );
+        assert!(ctl.set_property("foo.service", &[], false).is_err());
+
+        let values = ctl
+            .get_properties(
+                "foo.service",
+                &[Property::CpuQuota, Property::MemoryMax, Property::TasksMax],
+            )
+            .unwrap();
+        assert_eq!(values.len(), 2);
+        assert_eq!(values[&Property::CpuQuota], "500ms");
+        assert_eq!(values[&Property::MemoryMax], "536870912");
+    }
+}
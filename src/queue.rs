@@ -0,0 +1,319 @@
+//! Background execution of unit operations
+use crate::{Operation, RunResult, SystemCtl};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::{Arc, Condvar, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
+
+type Callback = Box<dyn FnOnce(&std::io::Result<RunResult>) + Send>;
+
+/// An operation waiting for the worker thread
+struct Task {
+    operation: Operation,
+    slot: Arc<Slot>,
+    callback: Option<Callback>,
+}
+
+/// Progress of a task
+#[derive(Default)]
+enum State {
+    #[default]
+    Pending,
+    /// Completed, its result was not taken yet
+    Done(std::io::Result<RunResult>),
+    /// Completed, its result was taken
+    Taken,
+}
+
+impl State {
+    /// Takes the result of a completed task
+    fn take(&mut self) -> Option<std::io::Result<RunResult>> {
+        match std::mem::take(self) {
+            State::Pending => None,
+            State::Done(result) => {
+                *self = State::Taken;
+                Some(result)
+            },
+            State::Taken => {
+                *self = State::Taken;
+                None
+            },
+        }
+    }
+}
+
+/// Outcome of a task, filled by the worker thread
+#[derive(Default)]
+struct Slot {
+    state: Mutex<State>,
+    done: Condvar,
+}
+
+impl Slot {
+    /// Stores the outcome and wakes up the waiting handles
+    fn fill(&self, result: std::io::Result<RunResult>) {
+        *self.state.lock().unwrap() = State::Done(result);
+        self.done.notify_all();
+    }
+}
+
+/// Tasks still queued when the worker exits, failed on drop
+/// so that no handle waits forever, even if the worker panicked
+struct Leftovers(Receiver<Task>);
+
+impl Drop for Leftovers {
+    fn drop(&mut self) {
+        for task in self.0.try_iter() {
+            task.slot.fill(Err(std::io::Error::other(
+                "background worker is not running",
+            )));
+        }
+    }
+}
+
+/// Applies [Operation]s on a background thread, one at a time and
+/// in submission order, so callers (like UI threads) never wait
+/// for `systemctl` to complete.
+///
+/// Pending operations still run when the `TaskQueue` is dropped,
+/// without blocking the dropping thread.
+///
+/// ```no_run
+/// let ctl = systemctl::SystemCtl::default();
+/// let queue = ctl.background();
+/// let handle = queue.restart("nginx.service");
+/// queue.submit_with(
+///     systemctl::Operation::Start("caddy.service".to_string()),
+///     |result| println!("caddy started: {}", result.is_ok()),
+/// );
+/// // later on
+/// if let Some(result) = handle.try_take() {
+///     println!("nginx restarted: {}", result.is_ok());
+/// }
+/// ```
+pub struct TaskQueue {
+    tasks: Option<Sender<Task>>,
+    worker: Option<JoinHandle<()>>,
+}
+
+impl TaskQueue {
+    fn new(ctl: SystemCtl) -> Self {
+        let (tasks, receiver) = mpsc::channel::<Task>();
+        let worker = thread::spawn(move || {
+            let leftovers = Leftovers(receiver);
+            while let Ok(task) = leftovers.0.recv() {
+                let result = ctl.apply(&task.operation);
+                if let Some(callback) = task.callback {
+                    // a panicking callback must not stop the queue
+                    let _ = panic::catch_unwind(AssertUnwindSafe(|| callback(&result)));
+                }
+                task.slot.fill(result);
+            }
+        });
+        Self {
+            tasks: Some(tasks),
+            worker: Some(worker),
+        }
+    }
+
+    /// Enqueues given `operation`
+    pub fn submit(&self, operation: Operation) -> TaskHandle {
+        self.enqueue(operation, None)
+    }
+
+    /// Enqueues given `operation`, `callback` is invoked on the
+    /// worker thread once it completed
+    pub fn submit_with<F>(&self, operation: Operation, callback: F) -> TaskHandle
+    where
+        F: FnOnce(&std::io::Result<RunResult>) + Send + 'static,
+    {
+        self.enqueue(operation, Some(Box::new(callback)))
+    }
+
+    /// Enqueues a `start` operation
+    pub fn start(&self, unit: &str) -> TaskHandle {
+        self.submit(Operation::Start(unit.to_string()))
+    }
+
+    /// Enqueues a `stop` operation
+    pub fn stop(&self, unit: &str) -> TaskHandle {
+        self.submit(Operation::Stop(unit.to_string()))
+    }
+
+    /// Enqueues a `restart` operation
+    pub fn restart(&self, unit: &str) -> TaskHandle {
+        self.submit(Operation::Restart(unit.to_string()))
+    }
+
+    /// Enqueues an `enable` operation
+    pub fn enable(&self, unit: &str) -> TaskHandle {
+        self.submit(Operation::Enable(unit.to_string()))
+    }
+
+    /// Enqueues a `disable` operation
+    pub fn disable(&self, unit: &str) -> TaskHandle {
+        self.submit(Operation::Disable(unit.to_string()))
+    }
+
+    /// Waits for all pending operations to complete
+    pub fn shutdown(mut self) {
+        self.tasks.take();
+        if let Some(worker) = self.worker.take() {
+            let _ = worker.join();
+        }
+    }
+
+    fn enqueue(&self, operation: Operation, callback: Option<Callback>) -> TaskHandle {
+        let slot = Arc::new(Slot::default());
+        let task = Task {
+            operation: operation.clone(),
+            slot: slot.clone(),
+            callback,
+        };
+        let sent = self.tasks.as_ref().map(|tasks| tasks.send(task));
+        if !matches!(sent, Some(Ok(()))) {
+            // worker is gone
+            slot.fill(Err(std::io::Error::other(
+                "background worker is not running",
+            )));
+        }
+        TaskHandle { operation, slot }
+    }
+}
+
+impl std::fmt::Debug for TaskQueue {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskQueue").finish_non_exhaustive()
+    }
+}
+
+impl Drop for TaskQueue {
+    fn drop(&mut self) {
+        // closing the channel lets the worker exit once idle
+        self.tasks.take();
+    }
+}
+
+/// Handle on an operation submitted to a [TaskQueue]
+pub struct TaskHandle {
+    operation: Operation,
+    slot: Arc<Slot>,
+}
+
+impl TaskHandle {
+    /// Submitted operation
+    pub fn operation(&self) -> &Operation {
+        &self.operation
+    }
+
+    /// Returns `true` once the operation completed
+    /// and its result was not taken yet
+    pub fn is_done(&self) -> bool {
+        matches!(*self.slot.state.lock().unwrap(), State::Done(_))
+    }
+
+    /// Takes the result of the operation, if it completed
+    /// and was not taken yet
+    pub fn try_take(&self) -> Option<std::io::Result<RunResult>> {
+        self.slot.state.lock().unwrap().take()
+    }
+
+    /// Blocks until the operation completed, returns its result.
+    /// Fails right away if the result was already taken by [TaskHandle::try_take]
+    /// or [TaskHandle::wait_timeout].
+    pub fn wait(self) -> std::io::Result<RunResult> {
+        let state = self.slot.state.lock().unwrap();
+        let mut state = self
+            .slot
+            .done
+            .wait_while(state, |state| matches!(state, State::Pending))
+            .unwrap();
+        state
+            .take()
+            .unwrap_or_else(|| Err(std::io::Error::other("task result already taken")))
+    }
+
+    /// Waits at most `timeout` for the operation to complete.
+    /// Returns `None` on timeout, or if the result was already taken.
+    pub fn wait_timeout(&self, timeout: Duration) -> Option<std::io::Result<RunResult>> {
+        let state = self.slot.state.lock().unwrap();
+        let (mut state, _) = self
+            .slot
+            .done
+            .wait_timeout_while(state, timeout, |state| matches!(state, State::Pending))
+            .unwrap();
+        state.take()
+    }
+}
+
+impl std::fmt::Debug for TaskHandle {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("TaskHandle")
+            .field("operation", &self.operation)
+            .field("done", &self.is_done())
+            .finish()
+    }
+}
+
+impl SystemCtl {
+    /// Creates a [TaskQueue] applying operations with a clone of self
+    /// on a dedicated thread
+    pub fn background(&self) -> TaskQueue {
+        TaskQueue::new(self.clone())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+    use std::sync::atomic::{AtomicUsize, Ordering};
+
+    #[test]
+    fn test_task_queue() {
+        // succeeding on `start` only
+        let mock = MockExecutor::new();
+        mock.on(&["start"], MockResponse::success("started\n"))
+            .on(&["stop"], MockResponse::failure(1, "Failed to stop"));
+        let queue = mock.ctl().background();
+        let calls = Arc::new(AtomicUsize::new(0));
+        let counter = calls.clone();
+        let start = queue.submit_with(Operation::Start("foo.service".into()), move |result| {
+            assert!(result.is_ok());
+            counter.fetch_add(1, Ordering::SeqCst);
+        });
+        let stop = queue.stop("foo.service");
+        assert_eq!(stop.operation(), &Operation::Stop("foo.service".into()));
+
+        let result = start.wait().unwrap();
+        assert_eq!(result.stdout, "started\n");
+        assert!(stop.wait_timeout(Duration::from_secs(5)).unwrap().is_err());
+        assert!(!stop.is_done());
+        // result already taken
+        assert!(stop.wait_timeout(Duration::from_secs(5)).is_none());
+        assert!(stop.try_take().is_none());
+        assert!(stop.wait().is_err());
+        queue.shutdown();
+        assert_eq!(calls.load(Ordering::SeqCst), 1);
+    }
+
+    #[test]
+    fn test_task_queue_panicking_callback() {
+        let mock = MockExecutor::new();
+        mock.on(&[], MockResponse::success(""))
+            .on(&["start"], MockResponse::success("started\n"));
+        let queue = mock.ctl().background();
+        let first = queue.submit_with(Operation::Start("foo.service".into()), |_| {
+            panic!("callback failure")
+        });
+        let second = queue.restart("foo.service");
+        let timeout = Duration::from_secs(5);
+        let result = first.wait_timeout(timeout).unwrap().unwrap();
+        assert_eq!(result.stdout, "started\n");
+        assert!(second.wait_timeout(timeout).unwrap().is_ok());
+        // worker is still running
+        assert!(queue.stop("foo.service").wait().is_ok());
+        queue.shutdown();
+    }
+}
@@ -0,0 +1,234 @@
+//! Start operations waiting for the unit to be ready
+use crate::{ActiveState, PasswordRequired, SystemCtl, UnitState};
+use std::io::{Error, ErrorKind};
+use std::thread;
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Interval between two state checks
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// Outcome of [SystemCtl::start_and_wait] and [SystemCtl::restart_and_wait]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum StartOutcome {
+    /// Unit is up and running, or ran to completion for oneshot services
+    Started,
+    /// Unit failed to start
+    Failed {
+        /// Error reported by `systemctl`, empty when the
+        /// failure was detected by polling
+        stderr: String,
+        /// Unit type specific state, like `failed` or `auto-restart`
+        sub_state: String,
+    },
+    /// Unit did not settle in time, the job keeps running
+    TimedOut {
+        /// Last observed state
+        state: UnitState,
+    },
+}
+
+impl StartOutcome {
+    /// Returns `true` if the unit started
+    pub fn is_started(&self) -> bool {
+        matches!(self, Self::Started)
+    }
+}
+
+impl SystemCtl {
+    /// Starts given `unit` and waits at most `timeout` for it to be
+    /// ready, that is for its start job to complete and the unit to be
+    /// `active`, or for the unit to fail. A unit back to `inactive`, like a
+    /// oneshot service, is started if it ran successfully (`Result=success`)
+    /// and was not skipped by its conditions (`ConditionResult=yes`).
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use systemctl::{StartOutcome, SystemCtl};
+    /// let ctl = SystemCtl::default();
+    /// match ctl.start_and_wait("nginx.service", Duration::from_secs(30)).unwrap() {
+    ///     StartOutcome::Started => println!("nginx is up"),
+    ///     StartOutcome::Failed { stderr, .. } => println!("nginx failed: {}", stderr),
+    ///     StartOutcome::TimedOut { state } => println!("nginx still {:?}", state.active),
+    /// }
+    /// ```
+    pub fn start_and_wait(
+        &self,
+        unit: impl AsRef<str>,
+        timeout: Duration,
+    ) -> std::io::Result<StartOutcome> {
+        self.job_and_wait("start", unit.as_ref(), timeout)
+    }
+
+    /// Restarts given `unit` and waits at most `timeout` for it
+    /// to be ready, see [SystemCtl::start_and_wait]
+    pub fn restart_and_wait(
+        &self,
+        unit: impl AsRef<str>,
+        timeout: Duration,
+    ) -> std::io::Result<StartOutcome> {
+        self.job_and_wait("restart", unit.as_ref(), timeout)
+    }
+
+    /// Runs the `verb` job on `unit` then polls its state until it settles
+    fn job_and_wait(
+        &self,
+        verb: &str,
+        unit: &str,
+        timeout: Duration,
+    ) -> std::io::Result<StartOutcome> {
+        let deadline = Instant::now() + timeout;
+        // both `systemctl` and backends return once the job completed
+        if let Some(backend) = self.backend()? {
+            let result = match verb {
+                "restart" => backend.restart(unit),
+                _ => backend.start(unit),
+            };
+            match result {
+                Err(e) if e.kind() == ErrorKind::Other => {
+                    let state = self.unit_state(unit)?;
+                    return Ok(StartOutcome::Failed {
+                        stderr: e.to_string(),
+                        sub_state: state.sub,
+                    });
+                },
+                result => result?,
+            };
+        } else {
+            let command = self.command([verb, unit]);
+            let output = match self.execute(command, Some(timeout)) {
+                // the job itself is left to systemd
+                Err(e) if e.kind() == ErrorKind::TimedOut => {
+                    return Ok(StartOutcome::TimedOut {
+                        state: self.unit_state(unit)?,
+                    })
+                },
+                result => result?,
+            };
+            if !output.status.success() {
+                let stderr = String::from_utf8_lossy(&output.stderr);
+                if PasswordRequired::matches(&stderr) {
+                    return Err(Error::new(
+                        ErrorKind::PermissionDenied,
+                        PasswordRequired::new(self, stderr.trim()),
+                    ));
+                }
+                let state = self.unit_state(unit)?;
+                return Ok(StartOutcome::Failed {
+                    stderr: stderr.trim().to_string(),
+                    sub_state: state.sub,
+                });
+            }
+        }
+        loop {
+            let state = self.unit_state(unit)?;
+            match state.active {
+                ActiveState::Active | ActiveState::Reloading => return Ok(StartOutcome::Started),
+                ActiveState::Failed => {
+                    return Ok(StartOutcome::Failed {
+                        stderr: String::new(),
+                        sub_state: state.sub,
+                    })
+                },
+                // oneshot services without `RemainAfterExit=` ran to completion,
+                // not to be confused with units skipped by a failed condition
+                ActiveState::Inactive => {
+                    let properties = self.show(unit, &["Result", "ConditionResult"])?;
+                    let is = |name: &str, expected: &str| {
+                        properties.get(name).is_none_or(|value| value == expected)
+                    };
+                    if is("Result", "success") && is("ConditionResult", "yes") {
+                        return Ok(StartOutcome::Started);
+                    }
+                    return Ok(StartOutcome::Failed {
+                        stderr: String::new(),
+                        sub_state: state.sub,
+                    });
+                },
+                _ => {},
+            }
+            if Instant::now() >= deadline {
+                return Ok(StartOutcome::TimedOut { state });
+            }
+            thread::sleep(POLL_INTERVAL);
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    /// `start` answers `start`, `show` reports the properties given by `state`
+    fn ctl(start: MockResponse, state: &str) -> SystemCtl {
+        let mock = MockExecutor::new();
+        mock.on(&["start"], start)
+            .on(&["show"], MockResponse::success(state));
+        mock.ctl()
+    }
+
+    #[test]
+    fn test_start_and_wait() {
+        let timeout = Duration::from_secs(5);
+        let ctl = self::ctl(
+            MockResponse::success(""),
+            "ActiveState=active\nSubState=running\n",
+        );
+        let outcome = ctl.start_and_wait("foo.service", timeout).unwrap();
+        assert!(outcome.is_started());
+
+        let ctl = self::ctl(
+            MockResponse::failure(1, "Job for foo.service failed.\n"),
+            "ActiveState=failed\nSubState=failed\n",
+        );
+        assert_eq!(
+            ctl.start_and_wait("foo.service", timeout).unwrap(),
+            StartOutcome::Failed {
+                stderr: "Job for foo.service failed.".to_string(),
+                sub_state: "failed".to_string(),
+            }
+        );
+
+        // oneshot service
+        let ctl = self::ctl(
+            MockResponse::success(""),
+            "ActiveState=inactive\nSubState=dead\nResult=success\nConditionResult=yes\n",
+        );
+        let outcome = ctl.start_and_wait("foo.service", timeout).unwrap();
+        assert!(outcome.is_started());
+
+        // skipped by a failed condition
+        let ctl = self::ctl(
+            MockResponse::success(""),
+            "ActiveState=inactive\nSubState=dead\nResult=success\nConditionResult=no\n",
+        );
+        assert_eq!(
+            ctl.start_and_wait("foo.service", timeout).unwrap(),
+            StartOutcome::Failed {
+                stderr: String::new(),
+                sub_state: "dead".to_string(),
+            }
+        );
+
+        let ctl = self::ctl(
+            MockResponse::hang(),
+            "ActiveState=activating\nSubState=start\n",
+        );
+        let outcome = ctl
+            .start_and_wait("foo.service", Duration::from_millis(300))
+            .unwrap();
+        assert_eq!(
+            outcome,
+            StartOutcome::TimedOut {
+                state: UnitState {
+                    active: ActiveState::Activating,
+                    sub: "start".to_string(),
+                }
+            }
+        );
+    }
+}
@@ -0,0 +1,183 @@
+//! Resource accounting of units (memory, CPU, tasks, IO)
+use crate::SystemCtl;
+use std::collections::HashMap;
+use std::num::ParseIntError;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Properties queried by [SystemCtl::resource_usage]
+pub(crate) const RESOURCE_PROPERTIES: &[&str] = &[
+    "MemoryCurrent",
+    "MemoryPeak",
+    "MemorySwapCurrent",
+    "CPUUsageNSec",
+    "TasksCurrent",
+    "IPIngressBytes",
+    "IPEgressBytes",
+    "IOReadBytes",
+    "IOWriteBytes",
+];
+
+/// Resources consumed by a unit, as accounted by systemd.
+/// Counters are `None` when accounting is disabled or not supported.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ResourceUsage {
+    /// Current memory usage, in bytes
+    pub memory_current: Option<u64>,
+    /// Highest memory usage, in bytes
+    pub memory_peak: Option<u64>,
+    /// Current swap usage, in bytes
+    pub swap_current: Option<u64>,
+    /// Consumed CPU time, in nanoseconds
+    pub cpu_usage_nsec: Option<u64>,
+    /// Current number of tasks (processes and threads)
+    pub tasks_current: Option<u64>,
+    /// Received IP traffic, in bytes
+    pub ip_ingress_bytes: Option<u64>,
+    /// Sent IP traffic, in bytes
+    pub ip_egress_bytes: Option<u64>,
+    /// Bytes read from block devices
+    pub io_read_bytes: Option<u64>,
+    /// Bytes written to block devices
+    pub io_write_bytes: Option<u64>,
+}
+
+impl ResourceUsage {
+    /// Builds from `systemctl show` properties, unknown or
+    /// unparsable properties are ignored
+    pub fn from_properties(properties: &HashMap<String, String>) -> Self {
+        let mut usage = Self::default();
+        for (key, value) in properties {
+            if let Some(counter) = usage.counter_mut(key) {
+                *counter = parse_counter(value).ok().flatten();
+            }
+        }
+        usage
+    }
+
+    /// Consumed CPU time
+    pub fn cpu_time(&self) -> Option<Duration> {
+        self.cpu_usage_nsec.map(Duration::from_nanos)
+    }
+
+    /// Counter matching given `systemctl show` property
+    pub(crate) fn counter_mut(&mut self, property: &str) -> Option<&mut Option<u64>> {
+        match property {
+            "MemoryCurrent" => Some(&mut self.memory_current),
+            "MemoryPeak" => Some(&mut self.memory_peak),
+            "MemorySwapCurrent" => Some(&mut self.swap_current),
+            "CPUUsageNSec" => Some(&mut self.cpu_usage_nsec),
+            "TasksCurrent" => Some(&mut self.tasks_current),
+            "IPIngressBytes" => Some(&mut self.ip_ingress_bytes),
+            "IPEgressBytes" => Some(&mut self.ip_egress_bytes),
+            "IOReadBytes" => Some(&mut self.io_read_bytes),
+            "IOWriteBytes" => Some(&mut self.io_write_bytes),
+            _ => None,
+        }
+    }
+}
+
+/// Parses an accounting counter of `systemctl show`,
+/// `None` when not available (`[not set]` or `UINT64_MAX`)
+pub(crate) fn parse_counter(value: &str) -> Result<Option<u64>, ParseIntError> {
+    match value.trim() {
+        "" | "[not set]" => Ok(None),
+        value => value.parse::<u64>().map(|n| (n != u64::MAX).then_some(n)),
+    }
+}
+
+/// Parses a size as printed by `systemctl status`, like `14.2M`, in bytes.
+/// A bare number is expressed in bytes.
+pub fn parse_bytes(value: &str) -> Option<u64> {
+    let value = value.trim();
+    if let Ok(bytes) = value.parse::<u64>() {
+        return Some(bytes);
+    }
+    let (index, suffix) = value.char_indices().last()?;
+    let factor: u64 = match suffix {
+        'B' => 1,
+        'K' => 1 << 10,
+        'M' => 1 << 20,
+        'G' => 1 << 30,
+        'T' => 1 << 40,
+        'P' => 1 << 50,
+        _ => return None,
+    };
+    let value: f64 = value[..index].parse().ok()?;
+    if !value.is_finite() || value < 0.0 {
+        return None;
+    }
+    // saturates on overflow
+    Some((value * factor as f64) as u64)
+}
+
+/// Formats a size like `systemctl status` does, like `14.2M`.
+/// Inverse of [parse_bytes], up to the single decimal.
+pub(crate) fn format_bytes(bytes: u64) -> String {
+    const SUFFIXES: [char; 6] = ['E', 'P', 'T', 'G', 'M', 'K'];
+    for (index, suffix) in SUFFIXES.iter().enumerate() {
+        let factor = 1u64 << (10 * (SUFFIXES.len() - index));
+        if bytes >= factor {
+            let tenths = u128::from(bytes) * 10 / u128::from(factor) % 10;
+            return format!("{}.{}{}", bytes / factor, tenths, suffix);
+        }
+    }
+    format!("{}B", bytes)
+}
+
+impl SystemCtl {
+    /// Returns the resources currently consumed by given `unit`
+    pub fn resource_usage(&self, unit: impl AsRef<str>) -> std::io::Result<ResourceUsage> {
+        let properties = self.show(unit.as_ref(), RESOURCE_PROPERTIES)?;
+        Ok(ResourceUsage::from_properties(&properties))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_resource_usage() {
+        let properties: HashMap<String, String> = [
+            ("MemoryCurrent", "14893056"),
+            ("MemoryPeak", "18446744073709551615"),
+            ("CPUUsageNSec", "1234000000"),
+            ("TasksCurrent", "3"),
+            ("IPIngressBytes", "[not set]"),
+            ("IOReadBytes", "garbage"),
+            ("Id", "foo.service"),
+        ]
+        .iter()
+        .map(|(k, v)| (k.to_string(), v.to_string()))
+        .collect();
+        let usage = ResourceUsage::from_properties(&properties);
+        assert_eq!(
+            usage,
+            ResourceUsage {
+                memory_current: Some(14893056),
+                cpu_usage_nsec: Some(1234000000),
+                tasks_current: Some(3),
+                ..Default::default()
+            }
+        );
+        assert_eq!(usage.cpu_time(), Some(Duration::from_millis(1234)));
+
+        assert_eq!(parse_bytes("1.5M"), Some(1572864));
+        assert_eq!(parse_bytes("512"), Some(512));
+        assert_eq!(parse_bytes("12X"), None);
+        assert_eq!(parse_bytes(""), None);
+        assert_eq!(parse_bytes("1é"), None);
+        assert_eq!(parse_bytes("é"), None);
+        assert_eq!(parse_bytes("-1M"), None);
+
+        assert_eq!(format_bytes(512), "512B");
+        assert_eq!(format_bytes(1261568), "1.2M");
+        assert_eq!(format_bytes(1572864), "1.5M");
+        assert_eq!(format_bytes(u64::MAX), "15.9E");
+        assert_eq!(parse_bytes(&format_bytes(1572864)), Some(1572864));
+    }
+}
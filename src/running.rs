@@ -0,0 +1,195 @@
+//! Runtime unit listing (`systemctl list-units`)
+use crate::{ActiveState, SystemCtl};
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A unit known to the service manager, as listed by `systemctl list-units`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct RunningUnit {
+    /// Unit name
+    pub name: String,
+    /// Load state (`loaded`, `not-found`, `masked`..)
+    pub load_state: String,
+    /// Activation state
+    pub active_state: ActiveState,
+    /// Unit type specific state (`running`, `exited`, `listening`..)
+    pub sub_state: String,
+    /// Unit description
+    pub description: String,
+}
+
+impl RunningUnit {
+    /// Parses the output of `systemctl list-units --json=short`.
+    /// Returns `None` if `content` is not a JSON listing.
+    pub fn parse_json(content: &str) -> Option<Vec<RunningUnit>> {
+        let units: Vec<serde_json::Map<String, serde_json::Value>> =
+            serde_json::from_str(content.trim()).ok()?;
+        let field = |unit: &serde_json::Map<String, serde_json::Value>, key: &str| {
+            unit.get(key)
+                .and_then(|v| v.as_str())
+                .unwrap_or_default()
+                .to_string()
+        };
+        Some(
+            units
+                .iter()
+                .map(|unit| RunningUnit {
+                    name: field(unit, "unit"),
+                    load_state: field(unit, "load"),
+                    active_state: ActiveState::from_str(&field(unit, "active")).unwrap_or_default(),
+                    sub_state: field(unit, "sub"),
+                    description: field(unit, "description"),
+                })
+                .collect(),
+        )
+    }
+
+    /// Parses the output of `systemctl list-units --no-legend --plain --full`
+    pub fn parse_list(content: &str) -> Vec<RunningUnit> {
+        content
+            .lines()
+            .filter_map(|line| {
+                // failed units may be flagged, unless `--plain` is used
+                let line = line.trim_start().trim_start_matches(['●', '*']);
+                let mut rest = line.trim_start();
+                let mut columns = Vec::with_capacity(4);
+                while columns.len() < 4 {
+                    let (column, tail) = rest.split_once(char::is_whitespace).unwrap_or((rest, ""));
+                    if column.is_empty() {
+                        return None;
+                    }
+                    columns.push(column);
+                    rest = tail.trim_start();
+                }
+                Some(RunningUnit {
+                    name: columns[0].to_string(),
+                    load_state: columns[1].to_string(),
+                    active_state: ActiveState::from_str(columns[2]).unwrap_or_default(),
+                    sub_state: columns[3].to_string(),
+                    description: rest.trim_end().to_string(),
+                })
+            })
+            .collect()
+    }
+}
+
+impl SystemCtl {
+    /// Lists the units currently known to the service manager,
+    /// with their runtime state (`systemctl list-units`).
+    /// Unlike [SystemCtl::list_units_full], this does not list unit files.
+    ///  + type filter: optional `--type` filter
+    ///  + state filter: optional `--state` filter
+    ///  + glob filter: optional unit name filter
+    pub fn list_running_units(
+        &self,
+        type_filter: Option<&str>,
+        state_filter: Option<&str>,
+        glob: Option<&str>,
+    ) -> std::io::Result<Vec<RunningUnit>> {
+        let mut args = Vec::new();
+        if let Some(filter) = type_filter {
+            args.push("--type");
+            args.push(filter);
+        }
+        if let Some(filter) = state_filter {
+            args.push("--state");
+            args.push(filter);
+        }
+        if let Some(glob) = glob {
+            args.push(glob);
+        }
+        self.list_runtime_units(&args)
+    }
+
+    /// Lists the units in failed state (`systemctl list-units --failed`)
+    pub fn list_failed_units(&self) -> std::io::Result<Vec<RunningUnit>> {
+        self.list_runtime_units(&["--failed"])
+    }
+
+    /// Invokes `list-units`, in JSON when supported (systemd 246+)
+    fn list_runtime_units(&self, filters: &[&str]) -> std::io::Result<Vec<RunningUnit>> {
+        let mut args = vec!["list-units", "--json=short"];
+        args.extend_from_slice(filters);
+        let content = self.systemctl_capture(args)?;
+        if content.exit_status.success() {
+            return RunningUnit::parse_json(&content.stdout).ok_or_else(|| {
+                Error::new(ErrorKind::InvalidData, "invalid list-units JSON output")
+            });
+        }
+        // older versions reject `--json`: fall back to the text output
+        if !content.stderr.contains("--json") {
+            return Err(Error::other(content.stderr.trim().to_string()));
+        }
+        let mut args = vec!["list-units", "--no-legend", "--plain", "--full"];
+        args.extend_from_slice(filters);
+        let content = self.systemctl_capture(args)?;
+        Ok(RunningUnit::parse_list(&content.stdout))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_parse_running_units() {
+        let expected = vec![
+            RunningUnit {
+                name: "cron.service".into(),
+                load_state: "loaded".into(),
+                active_state: ActiveState::Active,
+                sub_state: "running".into(),
+                description: "Regular background program processing daemon".into(),
+            },
+            RunningUnit {
+                name: "foo.service".into(),
+                load_state: "not-found".into(),
+                active_state: ActiveState::Failed,
+                sub_state: "failed".into(),
+                description: "foo.service".into(),
+            },
+        ];
+        let json = r#"[{"unit":"cron.service","load":"loaded","active":"active","sub":"running","description":"Regular background program processing daemon"},{"unit":"foo.service","load":"not-found","active":"failed","sub":"failed","description":"foo.service"}]"#;
+        assert_eq!(RunningUnit::parse_json(json).unwrap(), expected);
+
+        let text = "\
+cron.service loaded    active running Regular background program processing daemon
+● foo.service  not-found failed failed foo.service
+";
+        assert_eq!(RunningUnit::parse_list(text), expected);
+        assert!(RunningUnit::parse_json("unknown option --json").is_none());
+        assert!(RunningUnit::parse_list("").is_empty());
+    }
+
+    #[test]
+    fn test_list_failed_units() {
+        let mock = MockExecutor::new();
+        mock.on(
+            &["list-units", "--json=short"],
+            MockResponse::failure(1, "systemctl: unrecognized option '--json=short'\n"),
+        )
+        .on(
+            &["list-units", "--plain"],
+            MockResponse::success("● foo.service not-found failed failed foo.service\n"),
+        );
+        let units = mock.ctl().list_failed_units().unwrap();
+        assert_eq!(units.len(), 1);
+        assert_eq!(units[0].name, "foo.service");
+
+        // other failures are not retried
+        let mock = MockExecutor::new();
+        mock.on(&["list-units"], MockResponse::hang());
+        let e = mock.ctl().list_failed_units().unwrap_err();
+        assert_eq!(e.kind(), ErrorKind::TimedOut);
+        let mock = MockExecutor::new();
+        mock.on(&["list-units"], MockResponse::failure(1, "Access denied\n"));
+        let e = mock.ctl().list_failed_units().unwrap_err();
+        assert_eq!(e.to_string(), "Access denied");
+        assert_eq!(mock.calls().len(), 1);
+    }
+}
@@ -0,0 +1,146 @@
+//! Start rate limiting (`StartLimitBurst=`, `StartLimitIntervalSec=`)
+use crate::{parse_timespan, SystemCtl};
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::time::Duration;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Drop-in written by [SystemCtl::set_start_limit]
+const DROP_IN: &str = "50-start-limit.conf";
+
+/// Start rate limiting of a unit: a unit started more than `burst`
+/// times within `interval` is not permitted to start anymore,
+/// until the interval elapsed or the unit is reset.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StartLimit {
+    /// Maximal number of starts within `interval`
+    pub burst: Option<u32>,
+    /// Time window, a zero interval disables rate limiting
+    pub interval: Option<Duration>,
+}
+
+impl StartLimit {
+    /// Renders the `[Unit]` drop-in applying these limits.
+    /// Unset values are not overridden.
+    pub fn to_drop_in(&self) -> String {
+        let mut content = String::from("[Unit]\n");
+        if let Some(interval) = self.interval {
+            content.push_str(&format!(
+                "StartLimitIntervalSec={}us\n",
+                interval.as_micros()
+            ));
+        }
+        if let Some(burst) = self.burst {
+            content.push_str(&format!("StartLimitBurst={}\n", burst));
+        }
+        content
+    }
+}
+
+impl SystemCtl {
+    /// Returns the start rate limiting of given `unit`
+    pub fn start_limit(&self, unit: &str) -> std::io::Result<StartLimit> {
+        let properties = self.show(unit, &["StartLimitBurst", "StartLimitIntervalUSec"])?;
+        Ok(StartLimit {
+            burst: properties
+                .get("StartLimitBurst")
+                .and_then(|burst| burst.parse().ok()),
+            interval: properties
+                .get("StartLimitIntervalUSec")
+                .and_then(|interval| parse_timespan(interval)),
+        })
+    }
+
+    /// Adjusts the start rate limiting of given `unit` with a drop-in,
+    /// then reloads the manager. Values left to `None` are not overridden.
+    ///  + runtime: the drop-in is lost on reboot (`/run`)
+    ///
+    /// Returns the path of the drop-in. The limits are not exposed
+    /// by `systemctl set-property`, so this only applies on the local host.
+    pub fn set_start_limit(
+        &self,
+        unit: &str,
+        limit: &StartLimit,
+        runtime: bool,
+    ) -> std::io::Result<PathBuf> {
+        if limit.burst.is_none() && limit.interval.is_none() {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                "no start limit to apply",
+            ));
+        }
+        let path = self.start_limit_drop_in(unit, runtime)?;
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // never let the manager read a partially written drop-in
+        let tmp = path.with_extension("conf.tmp");
+        std::fs::write(&tmp, limit.to_drop_in())?;
+        std::fs::rename(&tmp, &path)?;
+        self.daemon_reload()?;
+        Ok(path)
+    }
+
+    /// Removes the drop-in written by [SystemCtl::set_start_limit],
+    /// restoring the limits of the unit file, then reloads the manager
+    pub fn remove_start_limit(&self, unit: &str, runtime: bool) -> std::io::Result<()> {
+        let path = self.start_limit_drop_in(unit, runtime)?;
+        match std::fs::remove_file(&path) {
+            Ok(()) => {},
+            Err(e) if e.kind() == ErrorKind::NotFound => return Ok(()),
+            Err(e) => return Err(e),
+        }
+        self.daemon_reload()?;
+        Ok(())
+    }
+
+    fn start_limit_drop_in(&self, unit: &str, runtime: bool) -> std::io::Result<PathBuf> {
+        if unit.is_empty() || unit.contains('/') {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid unit name \"{}\"", unit),
+            ));
+        }
+        Ok(self
+            .unit_dir(runtime)?
+            .join(format!("{}.d", unit))
+            .join(DROP_IN))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_start_limit_drop_in() {
+        let limit = StartLimit {
+            burst: Some(5),
+            interval: Some(Duration::from_secs(10)),
+        };
+        assert_eq!(
+            limit.to_drop_in(),
+            "[Unit]\nStartLimitIntervalSec=10000000us\nStartLimitBurst=5\n"
+        );
+        let limit = StartLimit {
+            burst: Some(3),
+            interval: None,
+        };
+        assert_eq!(limit.to_drop_in(), "[Unit]\nStartLimitBurst=3\n");
+
+        let ctl = SystemCtl::default();
+        assert_eq!(
+            ctl.start_limit_drop_in("foo.service", false).unwrap(),
+            PathBuf::from("/etc/systemd/system/foo.service.d/50-start-limit.conf")
+        );
+        assert!(ctl.start_limit_drop_in("../foo.service", false).is_err());
+        let remote = SystemCtl::builder()
+            .additional_args(vec![])
+            .host("root@web1".to_string())
+            .build();
+        assert!(remote.start_limit_drop_in("foo.service", true).is_err());
+    }
+}
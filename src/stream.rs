@@ -0,0 +1,230 @@
+//! Line by line reading of large `systemctl` outputs
+use crate::{check_exit_status, SystemCtl};
+use std::io::{BufRead, BufReader, Cursor, Read};
+use std::process::{Child, ExitStatus};
+use std::thread::{self, JoinHandle};
+
+/// Iterator over the output lines of a running `systemctl` command,
+/// see [SystemCtl::status_stream]. Lines are yielded as soon as they are
+/// printed, standard error is collected in the background. Once the output
+/// is exhausted, a failing exit code is reported as a last error item.
+/// `systemctl` is terminated when the iterator is dropped early.
+pub struct LineStream {
+    lines: std::io::Split<Box<dyn BufRead + Send>>,
+    source: Source,
+    ctl: SystemCtl,
+    done: bool,
+}
+
+/// Where the lines of a [LineStream] come from
+enum Source {
+    /// A running `systemctl`, its standard error being read by a thread
+    Process {
+        child: Child,
+        stderr: Option<JoinHandle<String>>,
+    },
+    /// Output of a completed command, returned by a [crate::SystemctlExecutor]
+    Completed { status: ExitStatus, stderr: String },
+}
+
+impl LineStream {
+    fn spawned(ctl: &SystemCtl, mut child: Child) -> std::io::Result<Self> {
+        let stdout = child.stdout.take().ok_or_else(|| {
+            std::io::Error::new(
+                std::io::ErrorKind::BrokenPipe,
+                "systemctl stdout not captured",
+            )
+        })?;
+        let stderr = child.stderr.take().map(|mut pipe| {
+            thread::spawn(move || {
+                let mut stderr = Vec::new();
+                let _ = pipe.read_to_end(&mut stderr);
+                String::from_utf8_lossy(&stderr).into_owned()
+            })
+        });
+        Ok(Self {
+            lines: (Box::new(BufReader::new(stdout)) as Box<dyn BufRead + Send>).split(b'\n'),
+            source: Source::Process { child, stderr },
+            ctl: ctl.clone(),
+            done: false,
+        })
+    }
+
+    fn completed(ctl: &SystemCtl, output: std::process::Output) -> Self {
+        Self {
+            lines: (Box::new(Cursor::new(output.stdout)) as Box<dyn BufRead + Send>).split(b'\n'),
+            source: Source::Completed {
+                status: output.status,
+                stderr: String::from_utf8_lossy(&output.stderr).into_owned(),
+            },
+            ctl: ctl.clone(),
+            done: false,
+        }
+    }
+
+    /// Waits for `systemctl` to exit and checks its exit code
+    fn finish(&mut self) -> std::io::Result<()> {
+        match &mut self.source {
+            Source::Process { child, stderr } => {
+                let status = child.wait()?;
+                let stderr = stderr
+                    .take()
+                    .and_then(|reader| reader.join().ok())
+                    .unwrap_or_default();
+                check_exit_status(&self.ctl, status, &stderr)
+            },
+            Source::Completed { status, stderr } => check_exit_status(&self.ctl, *status, stderr),
+        }
+    }
+}
+
+impl std::fmt::Debug for LineStream {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("LineStream")
+            .field("done", &self.done)
+            .finish_non_exhaustive()
+    }
+}
+
+impl Iterator for LineStream {
+    type Item = std::io::Result<String>;
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.done {
+            return None;
+        }
+        match self.lines.next() {
+            Some(Ok(line)) => Some(Ok(String::from_utf8_lossy(&line).into_owned())),
+            Some(Err(e)) => Some(Err(e)),
+            None => {
+                self.done = true;
+                self.finish().err().map(Err)
+            },
+        }
+    }
+}
+
+impl Drop for LineStream {
+    fn drop(&mut self) {
+        if let Source::Process { child, .. } = &mut self.source {
+            if !self.done {
+                let _ = child.kill();
+                let _ = child.wait();
+            }
+        }
+    }
+}
+
+impl SystemCtl {
+    /// Invokes `systemctl $args` and streams its output.
+    /// The `command_timeout` does not apply: output is consumed at the
+    /// caller's pace, who may drop the stream at any time. Commands run
+    /// by a custom [crate::SystemctlExecutor] are streamed once completed.
+    fn systemctl_stream<'a, 's: 'a, S: IntoIterator<Item = &'a str>>(
+        &'s self,
+        args: S,
+    ) -> std::io::Result<LineStream> {
+        match &self.executor {
+            Some(_) => {
+                let output = self.execute(self.command(args), self.command_timeout)?;
+                Ok(LineStream::completed(self, output))
+            },
+            None => LineStream::spawned(self, self.spawn_child(args)?),
+        }
+    }
+
+    /// Streams `systemctl status $unit` output, line by line.
+    /// `unit` may be a glob pattern matching many units.
+    ///
+    /// ```no_run
+    /// let ctl = systemctl::SystemCtl::default();
+    /// for line in ctl.status_stream("*.service").unwrap() {
+    ///     println!("{}", line.unwrap());
+    /// }
+    /// ```
+    pub fn status_stream(&self, unit: &str) -> std::io::Result<LineStream> {
+        self.systemctl_stream(["status", "--full", "--no-pager", unit])
+    }
+
+    /// Streams `systemctl cat $unit` output, line by line
+    pub fn cat_stream(&self, unit: &str) -> std::io::Result<LineStream> {
+        self.systemctl_stream(["cat", "--no-pager", unit])
+    }
+
+    /// Streams the units known to systemd (`systemctl list-units`), one line
+    /// per unit in the `UNIT LOAD ACTIVE SUB DESCRIPTION` format, without legend.
+    ///  + type filter: optional `--type` filter
+    ///  + state filter: optional `--state` filter
+    ///  + all: includes inactive units (`--all`)
+    pub fn list_units_stream(
+        &self,
+        type_filter: Option<&str>,
+        state_filter: Option<&str>,
+        all: bool,
+    ) -> std::io::Result<LineStream> {
+        let mut args = vec![
+            "list-units",
+            "--plain",
+            "--full",
+            "--no-legend",
+            "--no-pager",
+        ];
+        if let Some(filter) = type_filter {
+            args.push("--type");
+            args.push(filter);
+        }
+        if let Some(filter) = state_filter {
+            args.push("--state");
+            args.push(filter);
+        }
+        if all {
+            args.push("--all");
+        }
+        self.systemctl_stream(args)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_line_stream() {
+        // fake systemctl listing many units, then failing on `cat`
+        let script = "case \"$0\" in \
+            list-units) i=0; while [ $i -lt 20000 ]; do \
+                echo \"unit-$i.service loaded active running Unit $i\"; i=$((i+1)); done ;; \
+            cat) echo '# /etc/systemd/system/foo.service'; echo 'no access' >&2; exit 4 ;; \
+            esac";
+        let ctl = SystemCtl::builder()
+            .additional_args(vec!["-c".to_string(), script.to_string()])
+            .path("/bin/sh".to_string())
+            .build();
+        let mut count = 0;
+        for line in ctl.list_units_stream(None, None, true).unwrap() {
+            let line = line.unwrap();
+            assert!(line.starts_with(&format!("unit-{}.service ", count)));
+            count += 1;
+        }
+        assert_eq!(count, 20000);
+
+        // dropped early
+        let first = ctl
+            .list_units_stream(Some("service"), None, false)
+            .unwrap()
+            .next()
+            .unwrap()
+            .unwrap();
+        assert_eq!(first, "unit-0.service loaded active running Unit 0");
+
+        let lines: Vec<_> = ctl.cat_stream("foo.service").unwrap().collect();
+        assert_eq!(lines.len(), 2);
+        assert_eq!(
+            lines[0].as_ref().unwrap(),
+            "# /etc/systemd/system/foo.service"
+        );
+        assert_eq!(
+            lines[1].as_ref().unwrap_err().kind(),
+            std::io::ErrorKind::PermissionDenied
+        );
+    }
+}
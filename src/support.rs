@@ -0,0 +1,311 @@
+//! Diagnostic data collection, for bug reports
+use crate::{DependencyKind, DependencyNode, JournalCtl, JournalEntry, SystemCtl};
+use std::collections::hash_map::RandomState;
+use std::collections::BTreeMap;
+use std::fmt::Write as _;
+use std::fs;
+use std::hash::{BuildHasher, Hasher};
+use std::io::{Error, ErrorKind};
+use std::os::unix::fs::DirBuilderExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::SystemTime;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// `tar` program, resolved from `PATH`
+const TAR: &str = "tar";
+
+/// Everything known about a unit at a given time,
+/// see [SystemCtl::collect_support_bundle]
+#[derive(Clone, Debug, PartialEq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct SupportBundle {
+    /// Unit name
+    pub unit: String,
+    /// When the bundle was collected
+    pub collected_at: SystemTime,
+    /// `systemctl status` output, without log lines
+    pub status: String,
+    /// All properties (`systemctl show`)
+    pub properties: BTreeMap<String, String>,
+    /// Unit file and drop-ins, prefixed with their path (`systemctl cat`)
+    pub unit_files: String,
+    /// Most recent journal entries of the unit, oldest first
+    pub journal: Vec<JournalEntry>,
+    /// Units the unit depends on
+    pub dependencies: Option<DependencyNode>,
+    /// Units depending on the unit
+    pub reverse_dependencies: Option<DependencyNode>,
+    /// Failures met while collecting, one per missing section
+    pub errors: Vec<String>,
+}
+
+impl SupportBundle {
+    /// Writes the bundle as a gzipped tarball, holding one file
+    /// per section in a directory named after the unit. Journal
+    /// entries are stored like `journalctl -o json` prints them.
+    /// Files are staged in a private temporary directory,
+    /// then archived by the `tar` found in `PATH`.
+    pub fn write_tarball(&self, path: impl AsRef<Path>) -> std::io::Result<()> {
+        if self.unit.is_empty() || self.unit.contains('/') || self.unit.starts_with('.') {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid unit name \"{}\"", self.unit),
+            ));
+        }
+        let staging = private_temp_dir()?;
+        let result = self.stage(&staging).and_then(|_| {
+            let output = Command::new(TAR)
+                .arg("-czf")
+                .arg(path.as_ref())
+                .arg("-C")
+                .arg(&staging)
+                .arg(&self.unit)
+                .output()?;
+            if !output.status.success() {
+                return Err(Error::other(format!(
+                    "tar failed: {}",
+                    String::from_utf8_lossy(&output.stderr).trim()
+                )));
+            }
+            Ok(())
+        });
+        let _ = fs::remove_dir_all(&staging);
+        result
+    }
+
+    /// Writes the bundle files in `staging/$unit`
+    fn stage(&self, staging: &Path) -> std::io::Result<()> {
+        let dir = staging.join(&self.unit);
+        fs::DirBuilder::new().mode(0o700).create(&dir)?;
+        fs::write(dir.join("status.txt"), &self.status)?;
+        let mut properties = String::new();
+        for (key, value) in &self.properties {
+            let _ = writeln!(properties, "{}={}", key, value);
+        }
+        fs::write(dir.join("properties.txt"), properties)?;
+        fs::write(dir.join("unit-files.txt"), &self.unit_files)?;
+        let mut journal = String::new();
+        for entry in &self.journal {
+            let fields: serde_json::Map<String, serde_json::Value> = entry
+                .fields
+                .iter()
+                .map(|(k, v)| (k.clone(), serde_json::Value::String(v.clone())))
+                .collect();
+            let _ = writeln!(journal, "{}", serde_json::Value::Object(fields));
+        }
+        fs::write(dir.join("journal.json"), journal)?;
+        let mut dependencies = String::new();
+        for tree in [&self.dependencies, &self.reverse_dependencies]
+            .into_iter()
+            .flatten()
+        {
+            write_tree(&mut dependencies, tree, 0);
+            dependencies.push('\n');
+        }
+        fs::write(dir.join("dependencies.txt"), dependencies)?;
+        if !self.errors.is_empty() {
+            fs::write(dir.join("errors.txt"), self.errors.join("\n") + "\n")?;
+        }
+        Ok(())
+    }
+}
+
+/// Creates a new directory only accessible to the current user in the
+/// temporary directory, like `mkdtemp`: an existing path is never reused,
+/// so that other users cannot plant files or symlinks in it
+fn private_temp_dir() -> std::io::Result<PathBuf> {
+    let mut attempts = 0;
+    loop {
+        let mut hasher = RandomState::new().build_hasher();
+        hasher.write_u32(std::process::id());
+        hasher.write_u128(
+            SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap_or_default()
+                .as_nanos(),
+        );
+        let dir = std::env::temp_dir().join(format!("systemctl-bundle-{:016x}", hasher.finish()));
+        match fs::DirBuilder::new().mode(0o700).create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == ErrorKind::AlreadyExists && attempts < 16 => attempts += 1,
+            Err(e) => return Err(e),
+        }
+    }
+}
+
+/// Renders a dependency tree, indenting children by two spaces
+fn write_tree(content: &mut String, node: &DependencyNode, depth: usize) {
+    let _ = writeln!(content, "{:indent$}{}", "", node.unit, indent = depth * 2);
+    for child in &node.children {
+        write_tree(content, child, depth + 1);
+    }
+}
+
+impl SystemCtl {
+    /// Gathers the status, properties, unit files, last `lines` journal
+    /// entries and dependencies of given `unit`, for attaching to a bug report.
+    /// Fails if the unit properties cannot be retrieved. Other sections
+    /// are best effort: those that could not be retrieved are left empty
+    /// and the reason is recorded in [SupportBundle::errors].
+    ///
+    /// ```no_run
+    /// use systemctl::{JournalCtl, SystemCtl};
+    /// let ctl = SystemCtl::default();
+    /// let bundle = ctl
+    ///     .collect_support_bundle("nginx.service", &JournalCtl::default(), 200)
+    ///     .unwrap();
+    /// bundle.write_tarball("/tmp/nginx-bundle.tar.gz").unwrap();
+    /// ```
+    pub fn collect_support_bundle(
+        &self,
+        unit: impl AsRef<str>,
+        journal: &JournalCtl,
+        lines: usize,
+    ) -> std::io::Result<SupportBundle> {
+        let unit = unit.as_ref();
+        let properties = self.show(unit, &[])?.into_iter().collect();
+        let mut errors = Vec::new();
+        let mut record =
+            |section: &str, error: Error| errors.push(format!("{}: {}", section, error));
+
+        // non zero exit codes only reflect the unit state
+        let status = match self.systemctl_capture(["status", "--full", "--lines=0", unit]) {
+            Ok(result) => result.stdout,
+            Err(e) => {
+                record("status", e);
+                String::new()
+            },
+        };
+        let unit_files = match self.cat(unit) {
+            Ok(result) if result.exit_status.success() => result.stdout,
+            Ok(result) => {
+                record("unit files", Error::other(result.stderr.trim().to_string()));
+                String::new()
+            },
+            Err(e) => {
+                record("unit files", e);
+                String::new()
+            },
+        };
+        let journal = journal.unit_entries(unit, lines).unwrap_or_else(|e| {
+            record("journal", e);
+            Vec::new()
+        });
+        let mut dependencies = |reverse: bool| {
+            self.list_dependencies(unit, reverse, DependencyKind::Requirement, false)
+                .map_err(|e| {
+                    let section = if reverse {
+                        "reverse dependencies"
+                    } else {
+                        "dependencies"
+                    };
+                    record(section, e)
+                })
+                .ok()
+        };
+        let (dependencies, reverse_dependencies) = (dependencies(false), dependencies(true));
+        Ok(SupportBundle {
+            unit: unit.to_string(),
+            collected_at: SystemTime::now(),
+            status,
+            properties,
+            unit_files,
+            journal,
+            dependencies,
+            reverse_dependencies,
+            errors,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_support_bundle() {
+        // foo.service has no drop-in nor reverse dependency
+        let mock = MockExecutor::new();
+        let failed = MockResponse {
+            exit_code: 3,
+            stdout: "x foo.service - Foo\n".into(),
+            ..Default::default()
+        };
+        mock.on(
+            &["show"],
+            MockResponse::success("Id=foo.service\nActiveState=failed\n"),
+        )
+        .on(&["status"], failed)
+        .on(
+            &["cat"],
+            MockResponse::success("# /etc/systemd/system/foo.service\n[Service]\n"),
+        )
+        .on(
+            &["list-dependencies"],
+            MockResponse::success("foo.service\n  bar.service\n"),
+        )
+        .on(
+            &["list-dependencies", "--reverse"],
+            MockResponse::failure(1, "no reverse"),
+        )
+        .on(
+            &["--unit=foo.service"],
+            MockResponse::success("{\"MESSAGE\":\"crashed\",\"_SYSTEMD_UNIT\":\"foo.service\"}\n"),
+        );
+        let ctl = mock.ctl();
+        let bundle = ctl
+            .collect_support_bundle("foo.service", &ctl.journal(), 10)
+            .unwrap();
+        assert_eq!(bundle.properties["ActiveState"], "failed");
+        assert_eq!(bundle.status, "x foo.service - Foo\n");
+        assert!(bundle.unit_files.contains("[Service]"));
+        assert_eq!(bundle.journal.len(), 1);
+        assert_eq!(bundle.journal[0].message.as_deref(), Some("crashed"));
+        assert_eq!(
+            bundle.dependencies.as_ref().unwrap().units(),
+            vec!["bar.service"]
+        );
+        assert_eq!(bundle.reverse_dependencies, None);
+        assert_eq!(bundle.errors.len(), 1);
+        assert!(bundle.errors[0].starts_with("reverse dependencies: "));
+
+        let tarball =
+            std::env::temp_dir().join(format!("systemctl-bundle-{}.tar.gz", std::process::id()));
+        bundle.write_tarball(&tarball).unwrap();
+        let output = Command::new(TAR)
+            .arg("-tzf")
+            .arg(&tarball)
+            .output()
+            .unwrap();
+        let _ = fs::remove_file(&tarball);
+        let listing = String::from_utf8_lossy(&output.stdout);
+        for file in [
+            "status.txt",
+            "journal.json",
+            "dependencies.txt",
+            "errors.txt",
+        ] {
+            assert!(
+                listing.contains(&format!("foo.service/{}", file)),
+                "{}",
+                listing
+            );
+        }
+
+        // staging directories are fresh and private
+        use std::os::unix::fs::PermissionsExt;
+        let (first, second) = (private_temp_dir().unwrap(), private_temp_dir().unwrap());
+        assert_ne!(first, second);
+        let mode = fs::metadata(&first).unwrap().permissions().mode();
+        assert_eq!(mode & 0o777, 0o700);
+        let _ = (fs::remove_dir(first), fs::remove_dir(second));
+
+        let mut invalid = bundle.clone();
+        invalid.unit = "../etc".to_string();
+        assert!(invalid.write_tarball(&tarball).is_err());
+    }
+}
@@ -0,0 +1,313 @@
+//! Resource usage alerts on watched units
+use crate::resources::RESOURCE_PROPERTIES;
+use crate::{ResourceUsage, StateChange, SystemCtl, UnitState, UnitWatcher};
+use std::time::{Duration, Instant, SystemTime};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Resource measured by a [Threshold]
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Metric {
+    /// Current memory usage, in bytes
+    Memory,
+    /// CPU usage over the last polling interval, in percents
+    /// of a single CPU (200 is two CPUs fully busy)
+    CpuPercent,
+    /// Current number of tasks
+    Tasks,
+}
+
+/// Alerting rule: `metric` above `limit` for at least `sustained`
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct Threshold {
+    /// Measured resource
+    pub metric: Metric,
+    /// Highest tolerated value
+    pub limit: u64,
+    /// How long the limit must be exceeded before alerting,
+    /// `Duration::ZERO` alerts on first sample
+    pub sustained: Duration,
+}
+
+impl Threshold {
+    /// Memory usage above `bytes` for at least `sustained`
+    pub fn memory_above(bytes: u64, sustained: Duration) -> Self {
+        Self {
+            metric: Metric::Memory,
+            limit: bytes,
+            sustained,
+        }
+    }
+
+    /// CPU usage above `percent` of a CPU for at least `sustained`
+    pub fn cpu_above(percent: u64, sustained: Duration) -> Self {
+        Self {
+            metric: Metric::CpuPercent,
+            limit: percent,
+            sustained,
+        }
+    }
+
+    /// More than `tasks` tasks for at least `sustained`
+    pub fn tasks_above(tasks: u64, sustained: Duration) -> Self {
+        Self {
+            metric: Metric::Tasks,
+            limit: tasks,
+            sustained,
+        }
+    }
+}
+
+/// Whether a [ThresholdAlert] starts or ends
+#[derive(Copy, Clone, PartialEq, Eq, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum AlertState {
+    /// Limit exceeded for the sustained duration
+    Raised,
+    /// Back under the limit, after being raised
+    Cleared,
+}
+
+/// A [Threshold] crossing
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct ThresholdAlert {
+    /// Unit name
+    pub unit: String,
+    /// Crossed threshold
+    pub threshold: Threshold,
+    /// Raised or cleared
+    pub state: AlertState,
+    /// Measured value, `None` if no longer accounted
+    pub value: Option<u64>,
+    /// When the crossing was observed
+    pub timestamp: SystemTime,
+}
+
+/// Event of a [UnitWatcher] created by [SystemCtl::watch_thresholds]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum WatchEvent {
+    /// A watched unit changed state
+    StateChange(StateChange),
+    /// A threshold was crossed
+    Alert(ThresholdAlert),
+}
+
+/// Evaluates a [Threshold] against successive samples
+#[derive(Debug)]
+struct Tracker {
+    threshold: Threshold,
+    exceeded_since: Option<Instant>,
+    raised: bool,
+}
+
+impl Tracker {
+    fn new(threshold: Threshold) -> Self {
+        Self {
+            threshold,
+            exceeded_since: None,
+            raised: false,
+        }
+    }
+
+    /// Records a `value` sampled at `now`, returns the alert transition, if any
+    fn update(&mut self, value: Option<u64>, now: Instant) -> Option<AlertState> {
+        if value.is_some_and(|value| value > self.threshold.limit) {
+            let since = *self.exceeded_since.get_or_insert(now);
+            if !self.raised && now.duration_since(since) >= self.threshold.sustained {
+                self.raised = true;
+                return Some(AlertState::Raised);
+            }
+            None
+        } else {
+            self.exceeded_since = None;
+            std::mem::take(&mut self.raised).then_some(AlertState::Cleared)
+        }
+    }
+}
+
+/// A unit watched by [SystemCtl::watch_thresholds]
+#[derive(Debug)]
+struct WatchedUnit {
+    name: String,
+    state: UnitState,
+    trackers: Vec<Tracker>,
+    // previous CPU time sample, in nanoseconds
+    cpu_sample: Option<(u64, Instant)>,
+}
+
+impl WatchedUnit {
+    /// CPU usage since the previous sample, in percents of a CPU
+    fn cpu_percent(&mut self, cpu_nsec: Option<u64>, now: Instant) -> Option<u64> {
+        let previous = std::mem::replace(&mut self.cpu_sample, cpu_nsec.map(|cpu| (cpu, now)));
+        let (previous_nsec, previous_time) = previous?;
+        let elapsed = now.duration_since(previous_time).as_nanos();
+        let used = u128::from(cpu_nsec?.checked_sub(previous_nsec)?);
+        (elapsed > 0).then(|| (used * 100 / elapsed) as u64)
+    }
+}
+
+impl SystemCtl {
+    /// Watches the state and resource usage of units, polling them every
+    /// `interval`. Each `(unit, threshold)` pair raises a [ThresholdAlert]
+    /// once the threshold has been exceeded for its sustained duration,
+    /// and clears it when back under the limit. State changes of
+    /// the units are reported as well.
+    ///
+    /// ```no_run
+    /// use std::time::Duration;
+    /// use systemctl::{SystemCtl, Threshold, WatchEvent};
+    ///
+    /// let ctl = SystemCtl::default();
+    /// let five_minutes = Duration::from_secs(300);
+    /// let watcher = ctl
+    ///     .watch_thresholds(
+    ///         &[("nginx.service", Threshold::memory_above(1 << 30, five_minutes))],
+    ///         Duration::from_secs(10),
+    ///     )
+    ///     .unwrap();
+    /// for event in watcher {
+    ///     if let WatchEvent::Alert(alert) = event {
+    ///         println!("{}: {:?} {:?}", alert.unit, alert.state, alert.value);
+    ///     }
+    /// }
+    /// ```
+    pub fn watch_thresholds(
+        &self,
+        thresholds: &[(&str, Threshold)],
+        interval: Duration,
+    ) -> std::io::Result<UnitWatcher<WatchEvent>> {
+        let mut units: Vec<WatchedUnit> = Vec::new();
+        for (unit, threshold) in thresholds {
+            let tracker = Tracker::new(*threshold);
+            match units.iter_mut().find(|watched| watched.name == *unit) {
+                Some(watched) => watched.trackers.push(tracker),
+                None => units.push(WatchedUnit {
+                    name: unit.to_string(),
+                    state: self.unit_state(unit)?,
+                    trackers: vec![tracker],
+                    cpu_sample: None,
+                }),
+            }
+        }
+        let mut properties = vec!["ActiveState", "SubState"];
+        properties.extend_from_slice(RESOURCE_PROPERTIES);
+        let ctl = self.clone();
+        Ok(UnitWatcher::spawn(interval, move |events| {
+            for unit in units.iter_mut() {
+                // transient failures are retried on next poll
+                let Ok(mut values) = ctl.show(&unit.name, &properties) else {
+                    continue;
+                };
+                let now = Instant::now();
+                let usage = ResourceUsage::from_properties(&values);
+                let state = UnitState::from_properties(&mut values);
+                let mut pending = Vec::new();
+                if let Some(change) = StateChange::detect(&unit.name, &mut unit.state, state) {
+                    pending.push(WatchEvent::StateChange(change));
+                }
+                let cpu = unit.cpu_percent(usage.cpu_usage_nsec, now);
+                for tracker in unit.trackers.iter_mut() {
+                    let value = match tracker.threshold.metric {
+                        Metric::Memory => usage.memory_current,
+                        Metric::CpuPercent => cpu,
+                        Metric::Tasks => usage.tasks_current,
+                    };
+                    if let Some(state) = tracker.update(value, now) {
+                        pending.push(WatchEvent::Alert(ThresholdAlert {
+                            unit: unit.name.clone(),
+                            threshold: tracker.threshold,
+                            state,
+                            value,
+                            timestamp: SystemTime::now(),
+                        }));
+                    }
+                }
+                for event in pending {
+                    if events.send(event).is_err() {
+                        return false;
+                    }
+                }
+            }
+            true
+        }))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_threshold_tracker() {
+        let minute = Duration::from_secs(60);
+        let mut tracker = Tracker::new(Threshold::memory_above(1000, 5 * minute));
+        let start = Instant::now();
+        assert_eq!(tracker.update(Some(2000), start), None);
+        assert_eq!(tracker.update(Some(2000), start + 4 * minute), None);
+        assert_eq!(
+            tracker.update(Some(2000), start + 5 * minute),
+            Some(AlertState::Raised)
+        );
+        assert_eq!(tracker.update(Some(3000), start + 6 * minute), None);
+        assert_eq!(
+            tracker.update(Some(500), start + 7 * minute),
+            Some(AlertState::Cleared)
+        );
+        assert_eq!(tracker.update(None, start + 8 * minute), None);
+
+        // a short dip resets the sustained period
+        assert_eq!(tracker.update(Some(2000), start + 9 * minute), None);
+        assert_eq!(tracker.update(Some(10), start + 10 * minute), None);
+        assert_eq!(tracker.update(Some(2000), start + 14 * minute), None);
+    }
+
+    #[test]
+    fn test_watch_thresholds() {
+        let mut unit = WatchedUnit {
+            name: "foo.service".to_string(),
+            state: UnitState::default(),
+            trackers: Vec::new(),
+            cpu_sample: None,
+        };
+        let start = Instant::now();
+        assert_eq!(unit.cpu_percent(Some(0), start), None);
+        let second = Duration::from_secs(1);
+        assert_eq!(
+            unit.cpu_percent(Some(500_000_000), start + second),
+            Some(50)
+        );
+
+        // a running unit using 2 KiB of memory
+        let mock = MockExecutor::new();
+        mock.on(
+            &["show"],
+            MockResponse::success("ActiveState=active\nSubState=running\nMemoryCurrent=2048\n"),
+        );
+        let ctl = mock.ctl();
+        let threshold = Threshold::memory_above(1024, Duration::ZERO);
+        let watcher = ctl
+            .watch_thresholds(
+                &[
+                    ("foo.service", threshold),
+                    ("foo.service", Threshold::tasks_above(10, Duration::ZERO)),
+                ],
+                Duration::from_millis(10),
+            )
+            .unwrap();
+        let event = watcher.recv_timeout(Duration::from_secs(5)).unwrap();
+        let WatchEvent::Alert(alert) = event else {
+            panic!("unexpected event {:?}", event);
+        };
+        assert_eq!(alert.unit, "foo.service");
+        assert_eq!(alert.threshold, threshold);
+        assert_eq!(alert.state, AlertState::Raised);
+        assert_eq!(alert.value, Some(2048));
+        assert!(watcher.recv_timeout(Duration::from_millis(100)).is_none());
+    }
+}
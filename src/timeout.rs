@@ -0,0 +1,197 @@
+//! Bounded command execution
+use std::io::{Error, ErrorKind, Read};
+use std::process::{Child, Output};
+use std::sync::mpsc::{self, Sender};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Longest pause between two checks of a running command
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+/// How long output still buffered in the pipes of a killed command is collected
+const DRAIN_TIMEOUT: Duration = Duration::from_millis(100);
+
+/// Error payload returned (as [std::io::ErrorKind::TimedOut]) when a
+/// command did not complete within the configured `command_timeout`.
+/// The command is killed, its output up to that point is kept.
+///
+/// ```no_run
+/// use std::time::Duration;
+/// let ctl = systemctl::SystemCtl::builder()
+///     .additional_args(vec![])
+///     .command_timeout(Duration::from_secs(10))
+///     .build();
+/// if let Err(e) = ctl.stop("nfs-mount.mount") {
+///     if let Some(e) = e.get_ref().and_then(|e| e.downcast_ref::<systemctl::CommandTimedOut>()) {
+///         println!("systemctl hung, last output: {}", e.stderr);
+///     }
+/// }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct CommandTimedOut {
+    /// Exceeded timeout
+    pub timeout: Duration,
+    /// Standard output captured before the command was killed
+    pub stdout: String,
+    /// Standard error captured before the command was killed
+    pub stderr: String,
+}
+
+impl std::fmt::Display for CommandTimedOut {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "command timed out after {:?}", self.timeout)
+    }
+}
+
+impl std::error::Error for CommandTimedOut {}
+
+/// Output of a pipe, filled by a reader thread
+type Buffer = Arc<Mutex<Vec<u8>>>;
+
+/// Reads given pipe to its end in a new thread, signaling `done` when finished
+fn read_pipe<R: Read + Send + 'static>(pipe: Option<R>, done: Sender<()>) -> Buffer {
+    let buffer = Buffer::default();
+    let output = buffer.clone();
+    thread::spawn(move || {
+        if let Some(mut pipe) = pipe {
+            let mut chunk = [0; 4096];
+            while let Ok(n @ 1..) = pipe.read(&mut chunk) {
+                output.lock().unwrap().extend_from_slice(&chunk[..n]);
+            }
+        }
+        let _ = done.send(());
+    });
+    buffer
+}
+
+fn lossy(buffer: &Buffer) -> String {
+    String::from_utf8_lossy(&buffer.lock().unwrap()).into_owned()
+}
+
+/// Waits for `child` to complete while collecting its output. Both pipes
+/// are read concurrently, so that a verbose command cannot block on a full
+/// pipe. The child is killed once `timeout` is exceeded, if any. Pipes kept
+/// open by grandchildren once the child exited are drained until the deadline,
+/// plus [DRAIN_TIMEOUT].
+pub(crate) fn capture(mut child: Child, timeout: Option<Duration>) -> std::io::Result<Output> {
+    let (done, finished) = mpsc::channel();
+    let stdout = read_pipe(child.stdout.take(), done.clone());
+    let stderr = read_pipe(child.stderr.take(), done);
+    // waits for both readers, done once the pipes are closed
+    let drain = |until: Instant| {
+        for _ in 0..2 {
+            let left = until.saturating_duration_since(Instant::now());
+            if finished.recv_timeout(left).is_err() {
+                break;
+            }
+        }
+    };
+    let deadline = timeout.map(|timeout| Instant::now() + timeout);
+    let status = match timeout.zip(deadline) {
+        None => child.wait()?,
+        Some((timeout, deadline)) => {
+            let mut pause = Duration::from_millis(1);
+            loop {
+                if let Some(status) = child.try_wait()? {
+                    break status;
+                }
+                let now = Instant::now();
+                if now >= deadline {
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    // pipes may be kept open by grandchildren: draining is bounded
+                    drain(Instant::now() + DRAIN_TIMEOUT);
+                    return Err(Error::new(
+                        ErrorKind::TimedOut,
+                        CommandTimedOut {
+                            timeout,
+                            stdout: lossy(&stdout),
+                            stderr: lossy(&stderr),
+                        },
+                    ));
+                }
+                thread::sleep(pause.min(deadline - now));
+                pause = (pause * 2).min(MAX_POLL_INTERVAL);
+            }
+        },
+    };
+    let now = Instant::now();
+    drain(deadline.map_or(now, |deadline| deadline.max(now)) + DRAIN_TIMEOUT);
+    let take = |buffer: Buffer| std::mem::take(&mut *buffer.lock().unwrap());
+    Ok(Output {
+        status,
+        stdout: take(stdout),
+        stderr: take(stderr),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use crate::{CommandTimedOut, SystemCtl};
+    use std::io::ErrorKind;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn test_command_timeout() {
+        // fake systemctl: outputs a line then hangs, in a grandchild
+        let ctl = SystemCtl::builder()
+            .additional_args(vec![
+                "-c".to_string(),
+                "echo partial; echo stuck >&2; sleep 5".to_string(),
+            ])
+            .path("/bin/sh".to_string())
+            .command_timeout(Duration::from_millis(200))
+            .build();
+        let start = Instant::now();
+        let error = ctl.status("foo.service").unwrap_err();
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(error.kind(), ErrorKind::TimedOut);
+        let timed_out = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<CommandTimedOut>())
+            .unwrap();
+        assert_eq!(timed_out.timeout, Duration::from_millis(200));
+        assert_eq!(timed_out.stdout, "partial\n");
+        assert_eq!(timed_out.stderr, "stuck\n");
+
+        // per call override
+        let error = ctl
+            .with_timeout(Some(Duration::from_millis(300)))
+            .status("foo.service")
+            .unwrap_err();
+        let timed_out = error
+            .get_ref()
+            .and_then(|e| e.downcast_ref::<CommandTimedOut>())
+            .unwrap();
+        assert_eq!(timed_out.timeout, Duration::from_millis(300));
+    }
+
+    #[test]
+    fn test_capture_inherited_pipes() {
+        // exits in time, but a background process keeps the pipes open
+        let ctl = SystemCtl::builder()
+            .additional_args(vec!["-c".to_string(), "echo done; sleep 5 &".to_string()])
+            .path("/bin/sh".to_string())
+            .command_timeout(Duration::from_millis(200))
+            .build();
+        let start = Instant::now();
+        let result = ctl.status("foo.service").unwrap();
+        assert!(start.elapsed() < Duration::from_secs(2));
+        assert_eq!(result.stdout, "done\n");
+    }
+
+    #[test]
+    fn test_capture_large_output() {
+        // more output than a pipe can buffer, on both streams
+        let script = "head -c 200000 /dev/zero | tr '\\0' a; head -c 200000 /dev/zero >&2";
+        let ctl = SystemCtl::builder()
+            .additional_args(vec!["-c".to_string(), script.to_string()])
+            .path("/bin/sh".to_string())
+            .command_timeout(Duration::from_secs(30))
+            .build();
+        let result = ctl.status("foo.service").unwrap();
+        assert_eq!(result.stdout.len(), 200000);
+        assert_eq!(result.stderr.len(), 200000);
+    }
+}
@@ -0,0 +1,128 @@
+//! Timer listing, with parsed schedules
+use crate::{parse_timespan, parse_utc_timestamp, SystemCtl};
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A timer, as listed by `systemctl list-timers`
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct TimerEntry {
+    /// Next elapse time (UTC), `None` if the timer will not elapse
+    pub next: Option<SystemTime>,
+    /// Time left until next elapse, as reported when listing
+    pub left: Option<Duration>,
+    /// Last elapse time (UTC), `None` if the timer never elapsed
+    pub last: Option<SystemTime>,
+    /// Time passed since last elapse, as reported when listing
+    pub passed: Option<Duration>,
+    /// Timer unit name
+    pub unit: String,
+    /// Unit activated by this timer
+    pub activates: Option<String>,
+}
+
+impl TimerEntry {
+    /// Parses the output of `systemctl list-timers --timestamp=utc`.
+    /// The legend header is required, it describes the column layout.
+    pub fn parse_list(content: &str) -> Vec<TimerEntry> {
+        let mut lines = content.lines();
+        let Some(header) = lines.find(|line| line.trim_start().starts_with("NEXT")) else {
+            return Vec::new();
+        };
+        let columns: Vec<usize> = ["NEXT", "LEFT", "LAST", "PASSED", "UNIT", "ACTIVATES"]
+            .iter()
+            .filter_map(|column| header.find(column))
+            .map(|offset| header[..offset].chars().count())
+            .collect();
+        if columns.len() != 6 {
+            return Vec::new();
+        }
+        let mut timers = Vec::new();
+        // footer is separated by an empty line
+        for line in lines.take_while(|line| !line.trim().is_empty()) {
+            let chars: Vec<char> = line.chars().collect();
+            let field = |i: usize| -> Option<String> {
+                let start = columns[i].min(chars.len());
+                let end = columns
+                    .get(i + 1)
+                    .map_or(chars.len(), |end| (*end).min(chars.len()));
+                let value: String = chars[start..end].iter().collect();
+                let value = value.trim();
+                match value {
+                    "" | "-" | "n/a" => None,
+                    _ => Some(value.to_string()),
+                }
+            };
+            let Some(unit) = field(4) else {
+                continue;
+            };
+            timers.push(TimerEntry {
+                next: field(0).and_then(|t| parse_utc_timestamp(&t)),
+                left: field(1).and_then(|t| parse_timespan(t.trim_end_matches("left"))),
+                last: field(2).and_then(|t| parse_utc_timestamp(&t)),
+                passed: field(3).and_then(|t| parse_timespan(t.trim_end_matches("ago"))),
+                unit,
+                activates: field(5),
+            });
+        }
+        timers
+    }
+}
+
+impl SystemCtl {
+    /// Lists timers and their schedule (`systemctl list-timers`).
+    ///  + glob filter: optional timer name filter
+    ///  + all: also list inactive timers (`--all`)
+    pub fn list_timers(&self, glob: Option<&str>, all: bool) -> std::io::Result<Vec<TimerEntry>> {
+        let mut args = vec!["--full"];
+        if all {
+            args.push("--all");
+        }
+        if let Some(glob) = glob {
+            args.push(glob);
+        }
+        let content = self.systemctl_capture_utc("list-timers", &args)?;
+        Ok(TimerEntry::parse_list(&content.stdout))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_timer_list() {
+        let content = "\
+NEXT                        LEFT           LAST                        PASSED      UNIT                         ACTIVATES
+Fri 2024-01-12 00:00:00 UTC 13h left       Thu 2024-01-11 00:00:00 UTC 11h ago     logrotate.timer              logrotate.service
+Fri 2024-01-12 10:49:41 UTC 23h 59min left Thu 2024-01-11 10:49:41 UTC 2min 3s ago systemd-tmpfiles-clean.timer systemd-tmpfiles-clean.service
+-                           -              -                           -           foo.timer                    foo.service
+
+3 timers listed.
+Pass --all to see loaded but inactive timers, too.";
+        let timers = TimerEntry::parse_list(content);
+        assert_eq!(timers.len(), 3);
+        assert_eq!(
+            timers[0],
+            TimerEntry {
+                next: parse_utc_timestamp("2024-01-12 00:00:00 UTC"),
+                left: Some(Duration::from_secs(13 * 3600)),
+                last: parse_utc_timestamp("2024-01-11 00:00:00 UTC"),
+                passed: Some(Duration::from_secs(11 * 3600)),
+                unit: "logrotate.timer".into(),
+                activates: Some("logrotate.service".into()),
+            }
+        );
+        assert_eq!(
+            timers[1].left,
+            Some(Duration::from_secs(23 * 3600 + 59 * 60))
+        );
+        assert_eq!(timers[1].passed, Some(Duration::from_secs(123)));
+        assert_eq!(timers[2].unit, "foo.timer");
+        assert_eq!(timers[2].next, None);
+        assert_eq!(timers[2].left, None);
+        assert!(TimerEntry::parse_list("0 timers listed.").is_empty());
+    }
+}
@@ -0,0 +1,300 @@
+//! Timezone independent timestamp and time span parsing.
+//!
+//! systemd prints timestamps in the local timezone of the machine
+//! unless told otherwise. This crate always requests `--timestamp=utc`
+//! and exposes parsed times as [SystemTime], which is UTC by definition.
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// Parses a timestamp printed by systemd with `--timestamp=utc`
+/// (or `--timestamp=us+utc`), for example `Thu 2024-01-11 10:00:00 UTC`
+/// or `2024-01-11 10:00:00.123456 UTC`.
+///
+/// Returns `None` for `n/a`, empty values and timestamps that are not
+/// expressed in UTC, rather than guessing an offset.
+pub fn parse_utc_timestamp(s: &str) -> Option<SystemTime> {
+    let mut items: Vec<&str> = s.split_ascii_whitespace().collect();
+    if items.last() != Some(&"UTC") {
+        return None;
+    }
+    items.pop();
+    // leading day of week is optionnal
+    if items.len() == 3 {
+        items.remove(0);
+    }
+    if items.len() != 2 {
+        return None;
+    }
+
+    let mut date = items[0].splitn(3, '-');
+    let year: i64 = date.next()?.parse().ok()?;
+    let month: u32 = date.next()?.parse().ok()?;
+    let day: u32 = date.next()?.parse().ok()?;
+    if !(1..=12).contains(&month) || !(1..=31).contains(&day) {
+        return None;
+    }
+
+    let (time, micros) = match items[1].split_once('.') {
+        Some((time, frac)) => {
+            // right pad to microseconds: ".5" is 500000us
+            let frac = format!("{:0<6}", frac);
+            (time, frac.get(..6)?.parse::<u64>().ok()?)
+        },
+        None => (items[1], 0),
+    };
+    let mut time = time.splitn(3, ':');
+    let hours: u64 = time.next()?.parse().ok()?;
+    let minutes: u64 = time.next()?.parse().ok()?;
+    let seconds: u64 = time.next()?.parse().ok()?;
+    if hours > 23 || minutes > 59 || seconds > 60 {
+        return None;
+    }
+
+    let days = u64::try_from(days_from_civil(year, month, day)).ok()?;
+    let secs = days * 86400 + hours * 3600 + minutes * 60 + seconds;
+    Some(UNIX_EPOCH + Duration::from_secs(secs) + Duration::from_micros(micros))
+}
+
+/// Formats given time like systemd does with `--timestamp=utc`,
+/// for example `Thu 2024-01-11 10:00:00 UTC`. Sub-second precision is dropped.
+pub fn format_utc_timestamp(t: SystemTime) -> String {
+    let secs = match t.duration_since(UNIX_EPOCH) {
+        Ok(elapsed) => elapsed.as_secs() as i64,
+        Err(before) => -(before.duration().as_secs_f64().ceil() as i64),
+    };
+    let days = secs.div_euclid(86400);
+    let time = secs.rem_euclid(86400);
+    let (year, month, day) = civil_from_days(days);
+    let weekday =
+        ["Sun", "Mon", "Tue", "Wed", "Thu", "Fri", "Sat"][(days + 4).rem_euclid(7) as usize];
+    format!(
+        "{} {:04}-{:02}-{:02} {:02}:{:02}:{:02} UTC",
+        weekday,
+        year,
+        month,
+        day,
+        time / 3600,
+        time % 3600 / 60,
+        time % 60
+    )
+}
+
+/// Parses a raw microseconds-since-epoch value, as exposed
+/// by `systemctl show` for `*USec` properties and by `journalctl`.
+/// systemd uses `0` to describe an unset timestamp, which maps to `None`.
+pub fn parse_usec(s: &str) -> Option<SystemTime> {
+    match s.trim().parse::<u64>() {
+        Ok(0) | Err(_) => None,
+        Ok(usec) => Some(UNIX_EPOCH + Duration::from_micros(usec)),
+    }
+}
+
+/// Parses a time span as printed by systemd, like `500ms`, `1min 30s`
+/// or `2 weeks 1 day`. A bare number is expressed in seconds.
+/// Returns `None` for `infinity`, `n/a` and other unparsable values.
+pub fn parse_timespan(s: &str) -> Option<Duration> {
+    let mut rest = s.trim();
+    if rest.is_empty() {
+        return None;
+    }
+    // exact integer arithmetic, in nanoseconds
+    let mut total: u128 = 0;
+    while !rest.is_empty() {
+        let number_end = rest
+            .find(|c: char| !(c.is_ascii_digit() || c == '.'))
+            .unwrap_or(rest.len());
+        let (integer, fraction) = rest[..number_end]
+            .split_once('.')
+            .unwrap_or((&rest[..number_end], ""));
+        if integer.is_empty() && fraction.is_empty() {
+            return None;
+        }
+        rest = rest[number_end..].trim_start();
+        let unit_end = rest
+            .find(|c: char| !c.is_alphabetic())
+            .unwrap_or(rest.len());
+        let nanos: u128 = match &rest[..unit_end] {
+            "us" | "usec" | "μs" | "µs" => 1_000,
+            "ms" | "msec" => 1_000_000,
+            "" | "s" | "sec" | "second" | "seconds" => 1_000_000_000,
+            "m" | "min" | "minute" | "minutes" => 60_000_000_000,
+            "h" | "hr" | "hour" | "hours" => 3_600_000_000_000,
+            "d" | "day" | "days" => 86_400_000_000_000,
+            "w" | "week" | "weeks" => 604_800_000_000_000,
+            "M" | "month" | "months" => 2_629_800_000_000_000,
+            "y" | "year" | "years" => 31_557_600_000_000_000,
+            _ => return None,
+        };
+        let integer: u128 = if integer.is_empty() {
+            0
+        } else {
+            integer.parse().ok()?
+        };
+        // digits beyond the nanosecond resolution are dropped
+        let fraction = &fraction[..fraction.len().min(18)];
+        let scale = 10u128.pow(fraction.len() as u32);
+        let fraction: u128 = if fraction.is_empty() {
+            0
+        } else {
+            fraction.parse().ok()?
+        };
+        let value = integer
+            .checked_mul(nanos)?
+            .checked_add(fraction * nanos / scale)?;
+        total = total.checked_add(value)?;
+        rest = rest[unit_end..].trim_start();
+    }
+    let secs = u64::try_from(total / 1_000_000_000).ok()?;
+    Some(Duration::new(secs, (total % 1_000_000_000) as u32))
+}
+
+/// Formats a time span like `systemctl status` does, to the millisecond,
+/// like `123ms` or `2min 3.461s`. Inverse of [parse_timespan].
+pub(crate) fn format_timespan(span: Duration) -> String {
+    const UNITS: [(&str, u128); 9] = [
+        ("y", 31_557_600_000_000),
+        ("month", 2_629_800_000_000),
+        ("w", 604_800_000_000),
+        ("d", 86_400_000_000),
+        ("h", 3_600_000_000),
+        ("min", 60_000_000),
+        ("s", 1_000_000),
+        ("ms", 1_000),
+        ("us", 1),
+    ];
+    const ACCURACY: u128 = 1_000;
+    let mut usec = span.as_micros();
+    if usec == 0 {
+        return "0".to_string();
+    }
+    let mut parts = Vec::new();
+    for (suffix, unit) in UNITS {
+        if usec == 0 || (usec < ACCURACY && !parts.is_empty()) {
+            break;
+        }
+        if usec < unit {
+            continue;
+        }
+        let (whole, rest) = (usec / unit, usec % unit);
+        // below a minute, the remainder is shown as decimals
+        let digits = unit.ilog10().saturating_sub(ACCURACY.ilog10()) as usize;
+        if usec < 60_000_000 && rest > 0 && digits > 0 {
+            let decimals = rest / ACCURACY;
+            parts.push(format!("{}.{:0digits$}{}", whole, decimals, suffix));
+            break;
+        }
+        parts.push(format!("{}{}", whole, suffix));
+        usec = rest;
+    }
+    parts.join(" ")
+}
+
+/// Number of days between 1970-01-01 and given civil date
+/// (proleptic gregorian calendar).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let yoe = year.rem_euclid(400);
+    let month = i64::from(month);
+    let doy = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + i64::from(day) - 1;
+    let doe = yoe * 365 + yoe / 4 - yoe / 100 + doy;
+    era * 146097 + doe - 719468
+}
+
+/// Civil date of given number of days since 1970-01-01,
+/// inverse of [days_from_civil]
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719468;
+    let era = days.div_euclid(146097);
+    let doe = days.rem_euclid(146097);
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = (doy - (153 * mp + 2) / 5 + 1) as u32;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 } as u32;
+    let year = yoe + era * 400 + i64::from(month <= 2);
+    (year, month, day)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_utc_timestamp() {
+        let t = parse_utc_timestamp("Thu 2024-01-11 10:00:00 UTC").unwrap();
+        assert_eq!(t, UNIX_EPOCH + Duration::from_secs(1704967200));
+        let t = parse_utc_timestamp("1970-01-01 00:00:01.5 UTC").unwrap();
+        assert_eq!(t, UNIX_EPOCH + Duration::from_millis(1500));
+        let t = parse_utc_timestamp("Tue 2000-02-29 23:59:59.000001 UTC").unwrap();
+        assert_eq!(t, UNIX_EPOCH + Duration::from_micros(951868799000001));
+        assert!(parse_utc_timestamp("n/a").is_none());
+        assert!(parse_utc_timestamp("Thu 2024-01-11 10:00:00 CET").is_none());
+        assert!(parse_utc_timestamp("Thu 2024-13-11 10:00:00 UTC").is_none());
+    }
+
+    #[test]
+    fn test_format_utc_timestamp() {
+        let t = UNIX_EPOCH + Duration::from_secs(1704967200);
+        assert_eq!(format_utc_timestamp(t), "Thu 2024-01-11 10:00:00 UTC");
+        let t = UNIX_EPOCH + Duration::from_micros(951868799000001);
+        assert_eq!(format_utc_timestamp(t), "Tue 2000-02-29 23:59:59 UTC");
+        assert_eq!(
+            format_utc_timestamp(UNIX_EPOCH),
+            "Thu 1970-01-01 00:00:00 UTC"
+        );
+        for secs in [0, 59, 86399, 951868799, 1704967200, 4102444800] {
+            let t = UNIX_EPOCH + Duration::from_secs(secs);
+            assert_eq!(parse_utc_timestamp(&format_utc_timestamp(t)), Some(t));
+        }
+    }
+
+    #[test]
+    fn test_parse_timespan() {
+        assert_eq!(parse_timespan("500ms"), Some(Duration::from_millis(500)));
+        assert_eq!(parse_timespan("1min 30s"), Some(Duration::from_secs(90)));
+        assert_eq!(parse_timespan("10"), Some(Duration::from_secs(10)));
+        assert_eq!(
+            parse_timespan("2 weeks 1 day"),
+            Some(Duration::from_secs(15 * 86400))
+        );
+        assert_eq!(parse_timespan("1h 2min"), Some(Duration::from_secs(3720)));
+        assert_eq!(parse_timespan("123ms"), Some(Duration::from_millis(123)));
+        assert_eq!(parse_timespan("1.234s"), Some(Duration::from_millis(1234)));
+        assert_eq!(parse_timespan("1.5us"), Some(Duration::from_nanos(1500)));
+        assert_eq!(parse_timespan("99999999999999999999y"), None);
+        assert_eq!(parse_timespan("584942417356y"), None);
+        assert_eq!(parse_timespan("."), None);
+        assert_eq!(parse_timespan("infinity"), None);
+        assert_eq!(parse_timespan("n/a"), None);
+        assert_eq!(parse_timespan(""), None);
+    }
+
+    #[test]
+    fn test_format_timespan() {
+        let spans = [
+            (Duration::ZERO, "0"),
+            (Duration::from_micros(500), "500us"),
+            (Duration::from_millis(123), "123ms"),
+            (Duration::from_millis(1234), "1.234s"),
+            (Duration::from_millis(1020), "1.020s"),
+            (Duration::from_secs(1), "1s"),
+            (Duration::from_millis(123461), "2min 3.461s"),
+            (Duration::from_secs(90061), "1d 1h 1min 1s"),
+        ];
+        for (span, text) in spans {
+            assert_eq!(format_timespan(span), text);
+            assert_eq!(parse_timespan(text), Some(span));
+        }
+        assert_eq!(format_timespan(Duration::from_micros(1500)), "1ms");
+    }
+
+    #[test]
+    fn test_parse_usec() {
+        assert_eq!(
+            parse_usec("1704967200000000"),
+            Some(UNIX_EPOCH + Duration::from_secs(1704967200))
+        );
+        assert_eq!(parse_usec("0"), None);
+        assert_eq!(parse_usec("[not set]"), None);
+    }
+}
@@ -0,0 +1,382 @@
+//! Batches of unit operations that are rolled back on failure
+use crate::{EnablementState, RunResult, SystemCtl};
+use std::io::Error;
+
+/// Operation on a unit, that can be applied within a [Transaction]
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Operation {
+    /// `systemctl start $unit`
+    Start(String),
+    /// `systemctl stop $unit`
+    Stop(String),
+    /// `systemctl restart $unit`
+    Restart(String),
+    /// `systemctl enable $unit`
+    Enable(String),
+    /// `systemctl enable --runtime $unit`, until next reboot
+    EnableRuntime(String),
+    /// `systemctl disable $unit`
+    Disable(String),
+    /// `systemctl mask $unit`
+    Mask(String),
+    /// `systemctl mask --runtime $unit`, until next reboot
+    MaskRuntime(String),
+    /// `systemctl unmask $unit`
+    Unmask(String),
+}
+
+impl Operation {
+    /// Returns the unit this operation applies to
+    pub fn unit(&self) -> &str {
+        match self {
+            Self::Start(unit)
+            | Self::Stop(unit)
+            | Self::Restart(unit)
+            | Self::Enable(unit)
+            | Self::EnableRuntime(unit)
+            | Self::Disable(unit)
+            | Self::Mask(unit)
+            | Self::MaskRuntime(unit)
+            | Self::Unmask(unit) => unit,
+        }
+    }
+
+    /// Returns the `systemctl` verb of this operation
+    pub fn verb(&self) -> &'static str {
+        match self {
+            Self::Start(_) => "start",
+            Self::Stop(_) => "stop",
+            Self::Restart(_) => "restart",
+            Self::Enable(_) | Self::EnableRuntime(_) => "enable",
+            Self::Disable(_) => "disable",
+            Self::Mask(_) | Self::MaskRuntime(_) => "mask",
+            Self::Unmask(_) => "unmask",
+        }
+    }
+
+    /// `systemctl` arguments of this operation
+    fn args(&self) -> Vec<&str> {
+        match self {
+            Self::EnableRuntime(unit) | Self::MaskRuntime(unit) => {
+                vec![self.verb(), "--runtime", unit]
+            },
+            _ => vec![self.verb(), self.unit()],
+        }
+    }
+
+    /// `true` if this operation modifies the unit file state
+    fn changes_enablement(&self) -> bool {
+        !self.changes_activity()
+    }
+
+    /// `true` if this operation masks the unit
+    fn masks(&self) -> bool {
+        matches!(self, Self::Mask(_) | Self::MaskRuntime(_))
+    }
+
+    /// `true` if this operation modifies the runtime state
+    fn changes_activity(&self) -> bool {
+        matches!(self, Self::Start(_) | Self::Stop(_) | Self::Restart(_))
+    }
+}
+
+impl SystemCtl {
+    /// Applies given `operation`, a non successful exit code is reported as an error
+    pub fn apply(&self, operation: &Operation) -> std::io::Result<RunResult> {
+        let result = self.systemctl_capture(operation.args())?;
+        if !result.exit_status.success() {
+            return Err(Error::other(format!(
+                "{} {} failed: {}",
+                operation.verb(),
+                operation.unit(),
+                result.stderr.trim()
+            )));
+        }
+        Ok(result)
+    }
+
+    /// Creates a new [Transaction] bound to self
+    pub fn transaction(&self) -> Transaction<'_> {
+        Transaction {
+            ctl: self,
+            operations: Vec::new(),
+        }
+    }
+}
+
+/// State of a unit recorded before a [Transaction] is applied
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct UnitSnapshot {
+    /// Unit name
+    pub unit: String,
+    /// `systemctl is-enabled` state
+    pub enablement: EnablementState,
+    /// `true` if the unit was actively running
+    pub active: bool,
+}
+
+impl UnitSnapshot {
+    /// Operations restoring this snapshot, given the operations
+    /// that were (possibly partially) applied to this unit
+    fn rollback_operations(&self, applied: &[&Operation]) -> Vec<Operation> {
+        let unit = || self.unit.clone();
+        let mut ops = Vec::new();
+        if applied.iter().any(|op| op.changes_enablement()) {
+            let was_masked = self.enablement.is_masked();
+            if !was_masked && applied.iter().any(|op| op.masks()) {
+                ops.push(Operation::Unmask(unit()));
+            }
+            match self.enablement {
+                EnablementState::Enabled => ops.push(Operation::Enable(unit())),
+                EnablementState::EnabledRuntime => ops.push(Operation::EnableRuntime(unit())),
+                EnablementState::Disabled => ops.push(Operation::Disable(unit())),
+                EnablementState::Masked => ops.push(Operation::Mask(unit())),
+                EnablementState::MaskedRuntime => ops.push(Operation::MaskRuntime(unit())),
+                _ => {},
+            }
+        }
+        // a `start` leaves an active unit untouched, a `stop` an inactive one
+        let any = |matches: fn(&Operation) -> bool| applied.iter().any(|op| matches(op));
+        if self.active && any(|op| matches!(op, Operation::Stop(_) | Operation::Restart(_))) {
+            ops.push(Operation::Restart(unit()));
+        }
+        if !self.active && any(|op| matches!(op, Operation::Start(_) | Operation::Restart(_))) {
+            ops.push(Operation::Stop(unit()));
+        }
+        ops
+    }
+}
+
+/// A `Transaction` records the enablement and activity of every touched unit
+/// before applying a batch of [Operation]s. If any operation fails, the units
+/// touched so far are restored to their prior state (re-enabled, unmasked,
+/// restarted..) in reverse order.
+///
+/// ```no_run
+/// let ctl = systemctl::SystemCtl::default();
+/// ctl.transaction()
+///     .stop("nginx.service")
+///     .disable("nginx.service")
+///     .enable("caddy.service")
+///     .start("caddy.service")
+///     .commit()
+///     .unwrap();
+/// ```
+#[derive(Debug)]
+pub struct Transaction<'a> {
+    ctl: &'a SystemCtl,
+    operations: Vec<Operation>,
+}
+
+impl Transaction<'_> {
+    /// Appends an operation to this batch
+    pub fn push(mut self, operation: Operation) -> Self {
+        self.operations.push(operation);
+        self
+    }
+
+    /// Appends a `start` operation
+    pub fn start(self, unit: &str) -> Self {
+        self.push(Operation::Start(unit.to_string()))
+    }
+
+    /// Appends a `stop` operation
+    pub fn stop(self, unit: &str) -> Self {
+        self.push(Operation::Stop(unit.to_string()))
+    }
+
+    /// Appends a `restart` operation
+    pub fn restart(self, unit: &str) -> Self {
+        self.push(Operation::Restart(unit.to_string()))
+    }
+
+    /// Appends an `enable` operation
+    pub fn enable(self, unit: &str) -> Self {
+        self.push(Operation::Enable(unit.to_string()))
+    }
+
+    /// Appends a `disable` operation
+    pub fn disable(self, unit: &str) -> Self {
+        self.push(Operation::Disable(unit.to_string()))
+    }
+
+    /// Appends a `mask` operation
+    pub fn mask(self, unit: &str) -> Self {
+        self.push(Operation::Mask(unit.to_string()))
+    }
+
+    /// Appends an `unmask` operation
+    pub fn unmask(self, unit: &str) -> Self {
+        self.push(Operation::Unmask(unit.to_string()))
+    }
+
+    /// Operations of this batch, in application order
+    pub fn operations(&self) -> &[Operation] {
+        &self.operations
+    }
+
+    /// Records the state of every touched unit
+    fn snapshot(&self) -> std::io::Result<Vec<UnitSnapshot>> {
+        let mut snapshots: Vec<UnitSnapshot> = Vec::new();
+        for op in &self.operations {
+            if snapshots.iter().any(|s| s.unit == op.unit()) {
+                continue;
+            }
+            snapshots.push(UnitSnapshot {
+                unit: op.unit().to_string(),
+                enablement: self.ctl.enablement_state(op.unit())?,
+                active: self.ctl.is_active(op.unit())?,
+            });
+        }
+        Ok(snapshots)
+    }
+
+    /// Applies all operations in order.
+    ///
+    /// On failure, the touched units are rolled back and the returned error
+    /// carries a [TransactionError] (see [std::io::Error::get_ref]).
+    pub fn commit(self) -> std::io::Result<Vec<RunResult>> {
+        let snapshots = self.snapshot()?;
+        let mut results = Vec::with_capacity(self.operations.len());
+        for (index, op) in self.operations.iter().enumerate() {
+            match self.ctl.apply(op) {
+                Ok(result) => results.push(result),
+                Err(error) => {
+                    let kind = error.kind();
+                    let rollback_errors = self.rollback(&snapshots, &self.operations[..=index]);
+                    return Err(Error::new(
+                        kind,
+                        TransactionError {
+                            operation: op.clone(),
+                            error,
+                            rollback_errors,
+                        },
+                    ));
+                },
+            }
+        }
+        Ok(results)
+    }
+
+    /// Restores given snapshots, in reverse order.
+    /// Rollback keeps going on error, failures are reported.
+    fn rollback(
+        &self,
+        snapshots: &[UnitSnapshot],
+        applied: &[Operation],
+    ) -> Vec<(Operation, Error)> {
+        let mut errors = Vec::new();
+        for snapshot in snapshots.iter().rev() {
+            let applied: Vec<&Operation> = applied
+                .iter()
+                .filter(|op| op.unit() == snapshot.unit)
+                .collect();
+            for op in snapshot.rollback_operations(&applied) {
+                if let Err(e) = self.ctl.apply(&op) {
+                    errors.push((op, e));
+                }
+            }
+        }
+        errors
+    }
+}
+
+/// Describes a failed [Transaction]
+#[derive(Debug)]
+pub struct TransactionError {
+    /// Operation that failed
+    pub operation: Operation,
+    /// Failure cause
+    pub error: Error,
+    /// Rollback operations that failed as well, an empty list
+    /// means the prior state was fully restored
+    pub rollback_errors: Vec<(Operation, Error)>,
+}
+
+impl std::fmt::Display for TransactionError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "{} {}: {}",
+            self.operation.verb(),
+            self.operation.unit(),
+            self.error
+        )?;
+        if !self.rollback_errors.is_empty() {
+            write!(
+                f,
+                " ({} rollback operation(s) failed)",
+                self.rollback_errors.len()
+            )?;
+        }
+        Ok(())
+    }
+}
+
+impl std::error::Error for TransactionError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        Some(&self.error)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use std::str::FromStr;
+
+    fn snapshot(enablement: &str, active: bool) -> UnitSnapshot {
+        UnitSnapshot {
+            unit: "foo.service".to_string(),
+            enablement: EnablementState::from_str(enablement).unwrap(),
+            active,
+        }
+    }
+
+    #[test]
+    fn test_rollback_operations() {
+        let unit = || "foo.service".to_string();
+        let stop = Operation::Stop(unit());
+        let disable = Operation::Disable(unit());
+        let mask = Operation::Mask(unit());
+
+        let ops = snapshot("enabled", true).rollback_operations(&[&stop, &disable]);
+        assert_eq!(
+            ops,
+            vec![Operation::Enable(unit()), Operation::Restart(unit())]
+        );
+
+        let ops = snapshot("enabled", false).rollback_operations(&[&mask]);
+        assert_eq!(
+            ops,
+            vec![Operation::Unmask(unit()), Operation::Enable(unit())]
+        );
+
+        let ops = snapshot("masked", false).rollback_operations(&[&Operation::Unmask(unit())]);
+        assert_eq!(ops, vec![Operation::Mask(unit())]);
+
+        let ops =
+            snapshot("masked-runtime", false).rollback_operations(&[&Operation::Unmask(unit())]);
+        assert_eq!(ops, vec![Operation::MaskRuntime(unit())]);
+        assert_eq!(ops[0].args(), vec!["mask", "--runtime", "foo.service"]);
+
+        let ops = snapshot("enabled-runtime", true).rollback_operations(&[&disable]);
+        assert_eq!(ops, vec![Operation::EnableRuntime(unit())]);
+
+        let ops = snapshot("static", false).rollback_operations(&[&Operation::Start(unit())]);
+        assert_eq!(ops, vec![Operation::Stop(unit())]);
+
+        // already running
+        assert!(snapshot("static", true)
+            .rollback_operations(&[&Operation::Start(unit())])
+            .is_empty());
+        let ops = snapshot("static", true).rollback_operations(&[&Operation::Restart(unit())]);
+        assert_eq!(ops, vec![Operation::Restart(unit())]);
+        assert!(snapshot("static", false)
+            .rollback_operations(&[&stop])
+            .is_empty());
+
+        assert!(snapshot("enabled", true)
+            .rollback_operations(&[])
+            .is_empty());
+    }
+}
@@ -0,0 +1,515 @@
+//! Unit file authoring and installation
+use crate::{SystemCtl, UnitName};
+use bon::Builder;
+use std::io::{Error, ErrorKind};
+use std::path::PathBuf;
+use std::time::Duration;
+use strum_macros::EnumString;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Process startup type of a service (`Type=`)
+#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum ServiceType {
+    #[strum(serialize = "simple")]
+    Simple,
+    #[strum(serialize = "exec")]
+    Exec,
+    #[strum(serialize = "forking")]
+    Forking,
+    #[strum(serialize = "oneshot")]
+    Oneshot,
+    #[strum(serialize = "dbus")]
+    Dbus,
+    #[strum(serialize = "notify")]
+    Notify,
+    #[strum(serialize = "idle")]
+    Idle,
+}
+
+impl ServiceType {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::Simple => "simple",
+            Self::Exec => "exec",
+            Self::Forking => "forking",
+            Self::Oneshot => "oneshot",
+            Self::Dbus => "dbus",
+            Self::Notify => "notify",
+            Self::Idle => "idle",
+        }
+    }
+}
+
+/// When a service is restarted (`Restart=`)
+#[derive(Copy, Clone, PartialEq, Eq, EnumString, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum RestartPolicy {
+    #[strum(serialize = "no")]
+    No,
+    #[strum(serialize = "on-success")]
+    OnSuccess,
+    #[strum(serialize = "on-failure")]
+    OnFailure,
+    #[strum(serialize = "on-abnormal")]
+    OnAbnormal,
+    #[strum(serialize = "on-watchdog")]
+    OnWatchdog,
+    #[strum(serialize = "on-abort")]
+    OnAbort,
+    #[strum(serialize = "always")]
+    Always,
+}
+
+impl RestartPolicy {
+    fn as_str(&self) -> &'static str {
+        match self {
+            Self::No => "no",
+            Self::OnSuccess => "on-success",
+            Self::OnFailure => "on-failure",
+            Self::OnAbnormal => "on-abnormal",
+            Self::OnWatchdog => "on-watchdog",
+            Self::OnAbort => "on-abort",
+            Self::Always => "always",
+        }
+    }
+}
+
+/// Service manager instance a unit is installed in
+#[derive(Copy, Clone, PartialEq, Eq, Debug, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Scope {
+    /// System manager, in `/etc/systemd/system`
+    #[default]
+    System,
+    /// Manager of the calling user (`--user`), in `~/.config/systemd/user`
+    User,
+}
+
+/// Content of a `.service`, `.timer` or `.socket` unit file, see
+/// [UnitFile::render] and [SystemCtl::install_unit]. Fields that do not
+/// apply to the type of the unit are rejected when rendering.
+///
+/// ```
+/// use systemctl::{RestartPolicy, UnitFile};
+/// let unit = UnitFile::builder()
+///     .name("app.service".to_string())
+///     .description("My application".to_string())
+///     .exec_start("/usr/bin/app --port 8080".to_string())
+///     .restart(RestartPolicy::OnFailure)
+///     .user("app".to_string())
+///     .environment(vec![("RUST_LOG".to_string(), "info".to_string())])
+///     .wanted_by(vec!["multi-user.target".to_string()])
+///     .build();
+/// assert!(unit.render().unwrap().contains("ExecStart=/usr/bin/app --port 8080\n"));
+/// ```
+#[derive(Builder, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnitFile {
+    /// Unit name, like `app.service`
+    pub name: String,
+
+    // [Unit]
+    /// Human readable description (`Description=`)
+    pub description: Option<String>,
+    /// Documentation URIs (`Documentation=`)
+    #[builder(default)]
+    pub documentation: Vec<String>,
+    /// Units pulled in along with this one (`Wants=`)
+    #[builder(default)]
+    pub wants: Vec<String>,
+    /// Units required by this one (`Requires=`)
+    #[builder(default)]
+    pub requires: Vec<String>,
+    /// Units started before this one (`After=`)
+    #[builder(default)]
+    pub after: Vec<String>,
+    /// Units started after this one (`Before=`)
+    #[builder(default)]
+    pub before: Vec<String>,
+
+    // [Service]
+    /// Process startup type (`Type=`)
+    pub service_type: Option<ServiceType>,
+    /// Command line of the main process (`ExecStart=`), mandatory for services
+    pub exec_start: Option<String>,
+    /// Command reloading the configuration (`ExecReload=`)
+    pub exec_reload: Option<String>,
+    /// Command stopping the service (`ExecStop=`)
+    pub exec_stop: Option<String>,
+    /// Restart policy (`Restart=`)
+    pub restart: Option<RestartPolicy>,
+    /// Delay before restarting (`RestartSec=`)
+    pub restart_sec: Option<Duration>,
+    /// User running the processes (`User=`)
+    pub user: Option<String>,
+    /// Group running the processes (`Group=`)
+    pub group: Option<String>,
+    /// Working directory of the processes (`WorkingDirectory=`)
+    pub working_directory: Option<String>,
+    /// Environment variables of the processes (`Environment=`)
+    #[builder(default)]
+    pub environment: Vec<(String, String)>,
+
+    // [Timer]
+    /// Calendar event, like `daily` or `Mon *-*-* 04:00` (`OnCalendar=`)
+    pub on_calendar: Option<String>,
+    /// Delay after boot (`OnBootSec=`)
+    pub on_boot_sec: Option<Duration>,
+    /// Delay after the last activation of the unit (`OnUnitActiveSec=`)
+    pub on_unit_active_sec: Option<Duration>,
+    /// Catch up on runs missed while powered off (`Persistent=`)
+    pub persistent: Option<bool>,
+    /// Unit to activate, the matching `.service` by default (`Unit=`)
+    pub unit: Option<String>,
+
+    // [Socket]
+    /// Stream sockets to listen on, like `8080` or `/run/app.sock` (`ListenStream=`)
+    #[builder(default)]
+    pub listen_stream: Vec<String>,
+    /// Datagram sockets to listen on (`ListenDatagram=`)
+    #[builder(default)]
+    pub listen_datagram: Vec<String>,
+    /// Spawns a service instance per connection (`Accept=`)
+    pub accept: Option<bool>,
+
+    // [Install]
+    /// Units wanting this one once enabled (`WantedBy=`)
+    #[builder(default)]
+    pub wanted_by: Vec<String>,
+    /// Units requiring this one once enabled (`RequiredBy=`)
+    #[builder(default)]
+    pub required_by: Vec<String>,
+    /// Alternative names, created once enabled (`Alias=`)
+    #[builder(default)]
+    pub alias: Vec<String>,
+
+    /// Any other `(section, key, value)` setting, like
+    /// `("Service", "LimitNOFILE", "65536")`
+    #[builder(default)]
+    pub extra: Vec<(String, String, String)>,
+}
+
+/// `key=value` settings of a unit file section
+type Settings<'a> = Vec<(&'a str, String)>;
+
+impl UnitFile {
+    /// Renders the unit file content. Fails on invalid names, values
+    /// spanning several lines, settings that do not apply to the type
+    /// of the unit and missing mandatory settings.
+    pub fn render(&self) -> std::io::Result<String> {
+        let name = UnitName::parse(&self.name)?;
+        let invalid = |reason: String| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("unit \"{}\": {}", self.name, reason),
+            )
+        };
+        let mut unit = Vec::new();
+        push(&mut unit, "Description", self.description.clone());
+        push_list(&mut unit, "Documentation", &self.documentation);
+        push_list(&mut unit, "Wants", &self.wants);
+        push_list(&mut unit, "Requires", &self.requires);
+        push_list(&mut unit, "After", &self.after);
+        push_list(&mut unit, "Before", &self.before);
+
+        let mut service = Vec::new();
+        push(
+            &mut service,
+            "Type",
+            self.service_type.map(|t| t.as_str().to_string()),
+        );
+        push(&mut service, "ExecStart", self.exec_start.clone());
+        push(&mut service, "ExecReload", self.exec_reload.clone());
+        push(&mut service, "ExecStop", self.exec_stop.clone());
+        push(
+            &mut service,
+            "Restart",
+            self.restart.map(|r| r.as_str().to_string()),
+        );
+        push(&mut service, "RestartSec", self.restart_sec.map(timespan));
+        push(&mut service, "User", self.user.clone());
+        push(&mut service, "Group", self.group.clone());
+        push(
+            &mut service,
+            "WorkingDirectory",
+            self.working_directory.clone(),
+        );
+        if !self.environment.is_empty() {
+            let assignments: Vec<String> = self
+                .environment
+                .iter()
+                .map(|(key, value)| quote(&format!("{}={}", key, value)))
+                .collect();
+            service.push(("Environment", assignments.join(" ")));
+        }
+
+        let mut timer = Vec::new();
+        push(&mut timer, "OnCalendar", self.on_calendar.clone());
+        push(&mut timer, "OnBootSec", self.on_boot_sec.map(timespan));
+        push(
+            &mut timer,
+            "OnUnitActiveSec",
+            self.on_unit_active_sec.map(timespan),
+        );
+        push(&mut timer, "Persistent", self.persistent.map(yes_no));
+        push(&mut timer, "Unit", self.unit.clone());
+
+        let mut socket = Vec::new();
+        for listen in &self.listen_stream {
+            socket.push(("ListenStream", listen.clone()));
+        }
+        for listen in &self.listen_datagram {
+            socket.push(("ListenDatagram", listen.clone()));
+        }
+        push(&mut socket, "Accept", self.accept.map(yes_no));
+
+        let mut install = Vec::new();
+        push_list(&mut install, "WantedBy", &self.wanted_by);
+        push_list(&mut install, "RequiredBy", &self.required_by);
+        push_list(&mut install, "Alias", &self.alias);
+
+        let (specific, others) = match name.suffix() {
+            "service" => (("Service", service), [("Timer", timer), ("Socket", socket)]),
+            "timer" => (("Timer", timer), [("Service", service), ("Socket", socket)]),
+            "socket" => (("Socket", socket), [("Service", service), ("Timer", timer)]),
+            suffix => return Err(invalid(format!("unsupported unit type \"{}\"", suffix))),
+        };
+        for (section, settings) in &others {
+            if let Some((key, _)) = settings.first() {
+                return Err(invalid(format!(
+                    "{}= is only valid in [{}] sections",
+                    key, section
+                )));
+            }
+        }
+        let mandatory = match specific.0 {
+            "Service" => ["ExecStart"].as_slice(),
+            "Timer" => &["OnCalendar", "OnBootSec", "OnUnitActiveSec"],
+            _ => &["ListenStream", "ListenDatagram"],
+        };
+        if !specific.1.iter().any(|(key, _)| mandatory.contains(key)) {
+            return Err(invalid(format!("missing {}=", mandatory.join("= or "))));
+        }
+
+        // extra settings land in their section, or in new sections at the end
+        let mut sections: Vec<(&str, Settings)> =
+            vec![("Unit", unit), specific, ("Install", install)];
+        for (section, key, value) in &self.extra {
+            match sections.iter_mut().find(|(name, _)| name == section) {
+                Some((_, settings)) => settings.push((key, value.clone())),
+                None => sections.push((section, vec![(key, value.clone())])),
+            }
+        }
+
+        let mut content = String::new();
+        for (section, settings) in sections.iter().filter(|(_, s)| !s.is_empty()) {
+            if section.contains(['\n', '[', ']']) {
+                return Err(invalid(format!("invalid section name \"{}\"", section)));
+            }
+            if !content.is_empty() {
+                content.push('\n');
+            }
+            content.push_str(&format!("[{}]\n", section));
+            for (key, value) in settings {
+                if value.contains('\n') || key.contains(['\n', '=']) {
+                    return Err(invalid(format!("invalid {} setting", key)));
+                }
+                content.push_str(&format!("{}={}\n", key, value));
+            }
+        }
+        Ok(content)
+    }
+}
+
+/// Pushes `key=value`, if any
+fn push(settings: &mut Settings, key: &'static str, value: Option<String>) {
+    if let Some(value) = value {
+        settings.push((key, value));
+    }
+}
+
+/// Pushes `key=values..`, if any
+fn push_list(settings: &mut Settings, key: &'static str, values: &[String]) {
+    if !values.is_empty() {
+        settings.push((key, values.join(" ")));
+    }
+}
+
+fn timespan(duration: Duration) -> String {
+    format!("{}us", duration.as_micros())
+}
+
+fn yes_no(value: bool) -> String {
+    if value { "yes" } else { "no" }.to_string()
+}
+
+/// Quotes a word, escaping quotes and backslashes
+fn quote(word: &str) -> String {
+    format!("\"{}\"", word.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+impl SystemCtl {
+    /// Writes the given `unit` file in the unit directory of `scope`,
+    /// then reloads the manager. When `enable_now` is set, the unit is
+    /// also enabled and started (`systemctl enable --now`).
+    /// Returns the path of the unit file.
+    pub fn install_unit(
+        &self,
+        unit: &UnitFile,
+        scope: Scope,
+        enable_now: bool,
+    ) -> std::io::Result<PathBuf> {
+        let content = unit.render()?;
+        let ctl = self.with_scope(scope);
+        let dir = ctl.unit_dir(false)?;
+        std::fs::create_dir_all(&dir)?;
+        let path = dir.join(&unit.name);
+        // never let the manager read a partially written unit file
+        let tmp = dir.join(format!(".{}.tmp", unit.name));
+        std::fs::write(&tmp, content)?;
+        std::fs::rename(&tmp, &path)?;
+        ctl.daemon_reload()?;
+        if enable_now {
+            let result = ctl.systemctl_capture(["enable", "--now", &unit.name])?;
+            if !result.exit_status.success() {
+                return Err(Error::other(format!(
+                    "enable --now {} failed: {}",
+                    unit.name,
+                    result.stderr.trim()
+                )));
+            }
+        }
+        Ok(path)
+    }
+
+    /// Clone of self operating on the manager of given `scope`
+    fn with_scope(&self, scope: Scope) -> SystemCtl {
+        let mut ctl = self.clone();
+        ctl.additional_args
+            .retain(|arg| arg != "--user" && arg != "--system");
+        if scope == Scope::User {
+            ctl.additional_args.push("--user".to_string());
+        }
+        ctl
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_render_service() {
+        let unit = UnitFile::builder()
+            .name("app.service".to_string())
+            .description("My application".to_string())
+            .after(vec!["network-online.target".to_string()])
+            .wants(vec!["network-online.target".to_string()])
+            .service_type(ServiceType::Notify)
+            .exec_start("/usr/bin/app --port 8080".to_string())
+            .restart(RestartPolicy::OnFailure)
+            .restart_sec(Duration::from_secs(5))
+            .user("app".to_string())
+            .environment(vec![
+                ("RUST_LOG".to_string(), "info".to_string()),
+                ("GREETING".to_string(), "say \"hi\"".to_string()),
+            ])
+            .wanted_by(vec!["multi-user.target".to_string()])
+            .extra(vec![
+                ("Service".into(), "LimitNOFILE".into(), "65536".into()),
+                ("X-Custom".into(), "Owner".into(), "ops".into()),
+            ])
+            .build();
+        assert_eq!(
+            unit.render().unwrap(),
+            "\
+[Unit]
+Description=My application
+Wants=network-online.target
+After=network-online.target
+
+[Service]
+Type=notify
+ExecStart=/usr/bin/app --port 8080
+Restart=on-failure
+RestartSec=5000000us
+User=app
+Environment=\"RUST_LOG=info\" \"GREETING=say \\\"hi\\\"\"
+LimitNOFILE=65536
+
+[Install]
+WantedBy=multi-user.target
+
+[X-Custom]
+Owner=ops
+"
+        );
+    }
+
+    #[test]
+    fn test_render_timer_and_socket() {
+        let timer = UnitFile::builder()
+            .name("backup.timer".to_string())
+            .on_calendar("daily".to_string())
+            .persistent(true)
+            .wanted_by(vec!["timers.target".to_string()])
+            .build();
+        assert_eq!(
+            timer.render().unwrap(),
+            "[Timer]\nOnCalendar=daily\nPersistent=yes\n\n[Install]\nWantedBy=timers.target\n"
+        );
+        let socket = UnitFile::builder()
+            .name("app.socket".to_string())
+            .listen_stream(vec!["8080".to_string(), "/run/app.sock".to_string()])
+            .accept(false)
+            .build();
+        assert_eq!(
+            socket.render().unwrap(),
+            "[Socket]\nListenStream=8080\nListenStream=/run/app.sock\nAccept=no\n"
+        );
+    }
+
+    #[test]
+    fn test_render_errors() {
+        let invalid = [
+            // missing ExecStart=
+            UnitFile::builder().name("app.service".to_string()).build(),
+            // timer setting in a service
+            UnitFile::builder()
+                .name("app.service".to_string())
+                .exec_start("/usr/bin/app".to_string())
+                .on_calendar("daily".to_string())
+                .build(),
+            // multi line value
+            UnitFile::builder()
+                .name("app.service".to_string())
+                .exec_start("/usr/bin/app\nExecStartPre=/bin/rm".to_string())
+                .build(),
+            // unsupported type
+            UnitFile::builder().name("data.mount".to_string()).build(),
+            UnitFile::builder().name("app".to_string()).build(),
+        ];
+        for unit in invalid {
+            let err = unit.render().unwrap_err();
+            assert_eq!(err.kind(), ErrorKind::InvalidInput, "{}", err);
+        }
+    }
+
+    #[test]
+    fn test_with_scope() {
+        let ctl = SystemCtl::builder()
+            .additional_args(vec!["--user".to_string(), "--quiet".to_string()])
+            .build();
+        assert_eq!(
+            ctl.with_scope(Scope::System).additional_args,
+            vec!["--quiet"]
+        );
+        assert_eq!(
+            ctl.with_scope(Scope::User).additional_args,
+            vec!["--quiet", "--user"]
+        );
+    }
+}
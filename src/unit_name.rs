@@ -0,0 +1,396 @@
+//! Unit names, templates and instances (`foo@bar.service`)
+use crate::{Operation, SystemCtl, Type, Unit};
+use std::io::{Error, ErrorKind};
+use std::str::FromStr;
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Longest unit name accepted by systemd
+const UNIT_NAME_MAX: usize = 255;
+
+/// A validated unit name: plain (`foo.service`), template (`foo@.service`)
+/// or instance of a template (`foo@bar.service`).
+///
+/// Can be passed wherever a unit name is expected, like [SystemCtl::start]:
+/// ```no_run
+/// use systemctl::{SystemCtl, Type, UnitName};
+/// let unit = UnitName::instance("openvpn-client", "office/vpn", Type::Service).unwrap();
+/// assert_eq!(unit.as_str(), "openvpn-client@office-vpn.service");
+/// SystemCtl::default().start(&unit).unwrap();
+/// ```
+#[derive(Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "serde", serde(try_from = "String", into = "String"))]
+pub struct UnitName(String);
+
+impl UnitName {
+    /// Parses and validates given unit `name`
+    pub fn parse(name: &str) -> std::io::Result<Self> {
+        let invalid = |reason: &str| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("invalid unit name \"{}\": {}", name, reason),
+            )
+        };
+        if name.len() > UNIT_NAME_MAX {
+            return Err(invalid("too long"));
+        }
+        let (stem, suffix) = name
+            .rsplit_once('.')
+            .ok_or_else(|| invalid("missing type"))?;
+        if suffix.is_empty() || !suffix.chars().all(|c| c.is_ascii_lowercase()) {
+            return Err(invalid("bad type"));
+        }
+        let (prefix, _) = stem.split_once('@').unwrap_or((stem, ""));
+        if prefix.is_empty() {
+            return Err(invalid("empty prefix"));
+        }
+        if stem.matches('@').count() > 1 {
+            return Err(invalid("more than one '@'"));
+        }
+        if !stem
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || ":-_.\\@".contains(c))
+        {
+            return Err(invalid("forbidden character"));
+        }
+        Ok(Self(name.to_string()))
+    }
+
+    /// Builds the `prefix.type` unit name
+    pub fn new(prefix: &str, utype: Type) -> std::io::Result<Self> {
+        Self::parse(&format!("{}.{}", prefix, type_suffix(utype)))
+    }
+
+    /// Builds the `template@.type` template name
+    pub fn template(template: &str, utype: Type) -> std::io::Result<Self> {
+        Self::parse(&format!("{}@.{}", template, type_suffix(utype)))
+    }
+
+    /// Builds the `template@instance.type` unit name, `instance` is
+    /// escaped with [escape]
+    pub fn instance(template: &str, instance: &str, utype: Type) -> std::io::Result<Self> {
+        Self::parse(&format!(
+            "{}@{}.{}",
+            template,
+            escape(instance),
+            type_suffix(utype)
+        ))
+    }
+
+    /// Full unit name
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Name without type, and without instance for template units
+    /// (`foo` for `foo@bar.service`)
+    pub fn prefix(&self) -> &str {
+        let stem = self.stem();
+        stem.split_once('@').map_or(stem, |(prefix, _)| prefix)
+    }
+
+    /// Escaped instance, `None` for templates and plain units
+    pub fn instance_name(&self) -> Option<&str> {
+        self.stem()
+            .split_once('@')
+            .map(|(_, instance)| instance)
+            .filter(|instance| !instance.is_empty())
+    }
+
+    /// Unescaped instance, see [unescape]
+    pub fn instance_value(&self) -> Option<String> {
+        self.instance_name().map(unescape)
+    }
+
+    /// Type suffix (`service`, `socket`..)
+    pub fn suffix(&self) -> &str {
+        self.0.rsplit_once('.').map_or("", |(_, suffix)| suffix)
+    }
+
+    /// Unit type, errors on types unknown to [Type]
+    pub fn unit_type(&self) -> std::io::Result<Type> {
+        Type::from_unit_name(&self.0)
+    }
+
+    /// Returns `true` for templates (`foo@.service`)
+    pub fn is_template(&self) -> bool {
+        self.stem().ends_with('@')
+    }
+
+    /// Returns `true` for instances of a template (`foo@bar.service`)
+    pub fn is_instance(&self) -> bool {
+        self.instance_name().is_some()
+    }
+
+    /// Template this unit is an instance of, `None` for plain units
+    pub fn template_name(&self) -> Option<UnitName> {
+        self.stem()
+            .contains('@')
+            .then(|| Self(format!("{}@.{}", self.prefix(), self.suffix())))
+    }
+
+    /// Instantiates this template, or the template of this instance,
+    /// with given `instance`, escaped with [escape]
+    pub fn with_instance(&self, instance: &str) -> std::io::Result<UnitName> {
+        if !self.stem().contains('@') {
+            return Err(Error::new(
+                ErrorKind::InvalidInput,
+                format!("unit \"{}\" is not a template", self.0),
+            ));
+        }
+        Self::parse(&format!(
+            "{}@{}.{}",
+            self.prefix(),
+            escape(instance),
+            self.suffix()
+        ))
+    }
+
+    /// Name without type suffix
+    fn stem(&self) -> &str {
+        self.0.rsplit_once('.').map_or(&self.0, |(stem, _)| stem)
+    }
+}
+
+impl FromStr for UnitName {
+    type Err = Error;
+    fn from_str(name: &str) -> Result<Self, Self::Err> {
+        Self::parse(name)
+    }
+}
+
+impl TryFrom<String> for UnitName {
+    type Error = Error;
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        Self::parse(&name)
+    }
+}
+
+impl From<UnitName> for String {
+    fn from(name: UnitName) -> Self {
+        name.0
+    }
+}
+
+impl AsRef<str> for UnitName {
+    fn as_ref(&self) -> &str {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for UnitName {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// Unit name suffix of given type
+fn type_suffix(utype: Type) -> &'static str {
+    match utype {
+        Type::AutoMount => "automount",
+        Type::Mount => "mount",
+        Type::Service => "service",
+        Type::Scope => "scope",
+        Type::Socket => "socket",
+        Type::Slice => "slice",
+        Type::Timer => "timer",
+        Type::Path => "path",
+        Type::Target => "target",
+    }
+}
+
+/// Escapes `value` for use in unit names, like `systemd-escape` does:
+/// `/` becomes `-`, other characters than ASCII alphanumerics,
+/// `:`, `_` and `.` are hex encoded (`\x2d` for `-`)
+pub fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for (i, byte) in value.bytes().enumerate() {
+        match byte {
+            b'/' => escaped.push('-'),
+            b'.' if i == 0 => escaped.push_str("\\x2e"),
+            b if b.is_ascii_alphanumeric() || b":_.".contains(&b) => escaped.push(b as char),
+            b => escaped.push_str(&format!("\\x{:02x}", b)),
+        }
+    }
+    escaped
+}
+
+/// Escapes a file system `path` like `systemd-escape --path` does:
+/// redundant slashes are dropped and `/` becomes `-`
+pub fn escape_path(path: &str) -> String {
+    let path = path
+        .split('/')
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>()
+        .join("/");
+    if path.is_empty() {
+        "-".to_string()
+    } else {
+        escape(&path)
+    }
+}
+
+/// Reverts [escape]. Invalid escape sequences are kept as is.
+pub fn unescape(value: &str) -> String {
+    let bytes = value.as_bytes();
+    let mut unescaped = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+    while i < bytes.len() {
+        let decoded = (bytes[i] == b'\\' && bytes.get(i + 1) == Some(&b'x'))
+            .then(|| value.get(i + 2..i + 4))
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match (decoded, bytes[i]) {
+            (Some(byte), _) => {
+                unescaped.push(byte);
+                i += 4;
+                continue;
+            },
+            (None, b'-') => unescaped.push(b'/'),
+            (None, byte) => unescaped.push(byte),
+        }
+        i += 1;
+    }
+    String::from_utf8_lossy(&unescaped).into_owned()
+}
+
+impl SystemCtl {
+    /// Lists the active instances of given `template`, which may be
+    /// written `getty@`, `getty@.service` or simply `getty`
+    pub fn instances_of(&self, template: &str) -> std::io::Result<Vec<UnitName>> {
+        let glob = match template.rsplit_once('.') {
+            Some((stem, suffix)) => format!("{}@*.{}", stem.trim_end_matches('@'), suffix),
+            None => format!("{}@*", template.trim_end_matches('@')),
+        };
+        Ok(self
+            .list_running_units(None, None, Some(&glob))?
+            .into_iter()
+            .filter(|unit| unit.active_state.is_active())
+            .filter_map(|unit| UnitName::parse(&unit.name).ok())
+            .filter(UnitName::is_instance)
+            .collect())
+    }
+
+    /// Returns the `instance` of given `template`, escaped with [escape].
+    /// The template may be written `app@`, `app@.service`, or be any
+    /// instance of it, and must exist. When `start` is set, the
+    /// instance is enabled and started first.
+    ///
+    /// ```no_run
+    /// let ctl = systemctl::SystemCtl::default();
+    /// let unit = ctl.ensure_instance("app@", "tenant1", true).unwrap();
+    /// assert_eq!(unit.name, "app@tenant1.service");
+    /// ```
+    pub fn ensure_instance(
+        &self,
+        template: impl AsRef<str>,
+        instance: &str,
+        start: bool,
+    ) -> std::io::Result<Unit> {
+        let template = template.as_ref();
+        let name = if template.contains('.') {
+            UnitName::parse(template)?
+        } else {
+            UnitName::template(template.trim_end_matches('@'), Type::Service)?
+        };
+        let template = name.template_name().ok_or_else(|| {
+            Error::new(
+                ErrorKind::InvalidInput,
+                format!("unit \"{}\" is not a template", name),
+            )
+        })?;
+        if !self.exists(template.as_str())? {
+            return Err(Error::new(
+                ErrorKind::NotFound,
+                format!("template \"{}\" does not exist", template),
+            ));
+        }
+        let unit = template.with_instance(instance)?;
+        if start {
+            self.apply(&Operation::Enable(unit.to_string()))?;
+            self.apply(&Operation::Start(unit.to_string()))?;
+        }
+        self.create_unit(&unit)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_unit_name() {
+        let unit = UnitName::parse("getty@tty1.service").unwrap();
+        assert_eq!(unit.prefix(), "getty");
+        assert_eq!(unit.instance_name(), Some("tty1"));
+        assert_eq!(unit.suffix(), "service");
+        assert_eq!(unit.unit_type().unwrap(), Type::Service);
+        assert!(unit.is_instance() && !unit.is_template());
+        let template = unit.template_name().unwrap();
+        assert_eq!(template.as_str(), "getty@.service");
+        assert!(template.is_template() && !template.is_instance());
+        assert_eq!(
+            template.with_instance("tty2").unwrap().to_string(),
+            "getty@tty2.service"
+        );
+
+        let plain = UnitName::parse("cron.service").unwrap();
+        assert_eq!(plain.prefix(), "cron");
+        assert!(plain.template_name().is_none());
+        assert!(plain.with_instance("foo").is_err());
+        assert_eq!(UnitName::new("cron", Type::Service).unwrap(), plain);
+        assert_eq!(
+            UnitName::template("getty", Type::Service).unwrap(),
+            template
+        );
+
+        for invalid in ["cron", "@foo.service", "a@b@c.service", "foo bar.service"] {
+            assert!(UnitName::parse(invalid).is_err(), "{}", invalid);
+        }
+    }
+
+    #[test]
+    fn test_escape() {
+        assert_eq!(escape("foo/bar-baz"), "foo-bar\\x2dbaz");
+        assert_eq!(escape(".hidden file"), "\\x2ehidden\\x20file");
+        assert_eq!(escape_path("//mnt//data/"), "mnt-data");
+        assert_eq!(escape_path("/"), "-");
+        assert_eq!(unescape("foo-bar\\x2dbaz"), "foo/bar-baz");
+        assert_eq!(unescape("\\x2ehidden\\x20file"), ".hidden file");
+
+        let unit = UnitName::instance("openvpn-client", "office vpn", Type::Service).unwrap();
+        assert_eq!(unit.as_str(), "openvpn-client@office\\x20vpn.service");
+        assert_eq!(unit.instance_value().unwrap(), "office vpn");
+    }
+
+    #[test]
+    fn test_ensure_instance() {
+        // knowing the app@.service template only, which fails to start
+        let mock = MockExecutor::new();
+        mock.on(&["list-unit-files"], MockResponse::success(""))
+            .on(
+                &["list-unit-files", "app@.service"],
+                MockResponse::success("app@.service disabled -\n"),
+            )
+            .on(
+                &["show"],
+                MockResponse::success("Id=app@tenant\\x2d1.service\nLoadState=loaded\n"),
+            )
+            .on(&["start"], MockResponse::failure(1, ""));
+        let ctl = mock.ctl();
+        let unit = ctl.ensure_instance("app@", "tenant-1", false).unwrap();
+        assert_eq!(unit.name, "app@tenant\\x2d1.service");
+        assert!(ctl
+            .ensure_instance("app@.service", "tenant-1", false)
+            .is_ok());
+        assert!(ctl.ensure_instance("app@", "tenant-1", true).is_err());
+        let err = ctl.ensure_instance("web@", "tenant-1", false).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::NotFound);
+        assert!(ctl
+            .ensure_instance("app.service", "tenant-1", false)
+            .is_err());
+    }
+}
@@ -0,0 +1,105 @@
+//! Well-known warnings printed by `systemctl` on stderr
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// A warning reported by `systemctl` while the operation itself
+/// went through, see [RunResult::warnings](crate::RunResult::warnings)
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum Warning {
+    /// The unit file or its drop-ins changed on disk since
+    /// they were loaded: `systemctl daemon-reload` is due
+    UnitFileChanged {
+        /// Unit name
+        unit: String,
+    },
+    /// The unit was stopped, but the listed units can still activate it
+    StillTriggerable {
+        /// Unit name
+        unit: String,
+        /// Triggering units, like sockets or timers
+        triggers: Vec<String>,
+    },
+    /// Unit files have no `[Install]` section: enabling them is a no-op
+    NoInstallConfig,
+    /// Any other warning, as printed
+    Other(String),
+}
+
+impl Warning {
+    /// Extracts the warnings of a `systemctl` stderr output
+    pub fn parse(stderr: &str) -> Vec<Warning> {
+        let mut warnings = Vec::new();
+        let mut lines = stderr.lines().peekable();
+        while let Some(line) = lines.next() {
+            let line = line.trim();
+            if line.contains("changed on disk") {
+                if let Some(unit) = word_before(line, " changed on disk") {
+                    warnings.push(Warning::UnitFileChanged { unit });
+                    continue;
+                }
+            }
+            if let Some(rest) = line.strip_prefix("Warning: Stopping ") {
+                // followed by indented triggering units
+                let unit = rest.split(',').next().unwrap_or_default().to_string();
+                let mut triggers = Vec::new();
+                while let Some(next) = lines.next_if(|next| next.starts_with(char::is_whitespace)) {
+                    triggers.push(next.trim().to_string());
+                }
+                warnings.push(Warning::StillTriggerable { unit, triggers });
+            } else if line.starts_with("The unit files have no installation config") {
+                warnings.push(Warning::NoInstallConfig);
+            } else if let Some(warning) = line.strip_prefix("Warning: ") {
+                warnings.push(Warning::Other(warning.to_string()));
+            }
+        }
+        warnings
+    }
+}
+
+/// Returns the word preceding `pattern` in `line`, without trailing punctuation
+fn word_before(line: &str, pattern: &str) -> Option<String> {
+    let (before, _) = line.split_once(pattern)?;
+    let word = before.split_ascii_whitespace().last()?;
+    Some(word.trim_end_matches([',', '.', ':']).to_string())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_parse_warnings() {
+        let stderr = "\
+Warning: The unit file, source configuration file or drop-ins of foo.service changed on disk. Run 'systemctl daemon-reload' to reload units.
+Warning: Stopping baz.service, but it can still be activated by:
+  baz.socket
+  baz.timer
+The unit files have no installation config (WantedBy=, RequiredBy=, UpheldBy=,
+Warning: something else happened.";
+        assert_eq!(
+            Warning::parse(stderr),
+            vec![
+                Warning::UnitFileChanged {
+                    unit: "foo.service".into()
+                },
+                Warning::StillTriggerable {
+                    unit: "baz.service".into(),
+                    triggers: vec!["baz.socket".into(), "baz.timer".into()],
+                },
+                Warning::NoInstallConfig,
+                Warning::Other("something else happened.".into()),
+            ]
+        );
+        let legacy =
+            "Warning: foo.service changed on disk. Run 'systemctl daemon-reload' to reload units.";
+        assert_eq!(
+            Warning::parse(legacy),
+            vec![Warning::UnitFileChanged {
+                unit: "foo.service".into()
+            }]
+        );
+        assert!(Warning::parse("").is_empty());
+    }
+}
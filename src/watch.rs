@@ -0,0 +1,228 @@
+//! Unit state change notifications
+use crate::{ActiveState, SystemCtl};
+use std::collections::HashMap;
+use std::str::FromStr;
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread::{self, JoinHandle};
+use std::time::{Duration, SystemTime};
+
+#[cfg(feature = "serde")]
+use serde::{Deserialize, Serialize};
+
+/// Default polling interval of [SystemCtl::watch]
+const WATCH_INTERVAL: Duration = Duration::from_secs(1);
+
+/// State of a unit, as observed by a [UnitWatcher]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct UnitState {
+    /// High level state (`ActiveState`)
+    pub active: ActiveState,
+    /// Unit type specific state (`SubState`), like `running` or `exited`
+    pub sub: String,
+}
+
+/// A unit state transition
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub struct StateChange {
+    /// Unit name
+    pub unit: String,
+    /// State before the transition
+    pub old: UnitState,
+    /// State after the transition
+    pub new: UnitState,
+    /// When the transition was observed
+    pub timestamp: SystemTime,
+}
+
+/// Receives the events of watched units, state transitions
+/// by default (see [SystemCtl::watch]) or [WatchEvent](crate::WatchEvent)s
+/// (see [SystemCtl::watch_thresholds]).
+///
+/// Watching stops when the `UnitWatcher` is dropped.
+/// Iterating blocks until the next event.
+#[derive(Debug)]
+pub struct UnitWatcher<E = StateChange> {
+    events: Receiver<E>,
+    // dropping this channel wakes up and stops the polling thread
+    stop: Option<Sender<()>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl<E: Send + 'static> UnitWatcher<E> {
+    /// Runs `poll` on a dedicated thread every `interval`,
+    /// until it returns `false` or the watcher is dropped
+    pub(crate) fn spawn<F>(interval: Duration, mut poll: F) -> Self
+    where
+        F: FnMut(&Sender<E>) -> bool + Send + 'static,
+    {
+        let (events_tx, events) = mpsc::channel();
+        let (stop, stop_rx) = mpsc::channel::<()>();
+        let thread = thread::spawn(move || loop {
+            match stop_rx.recv_timeout(interval) {
+                Err(RecvTimeoutError::Timeout) => {},
+                _ => return,
+            }
+            if !poll(&events_tx) {
+                return;
+            }
+        });
+        Self {
+            events,
+            stop: Some(stop),
+            thread: Some(thread),
+        }
+    }
+}
+
+impl<E> UnitWatcher<E> {
+    /// Blocks until the next event. Returns `None`
+    /// if the polling thread stopped.
+    pub fn recv(&self) -> Option<E> {
+        self.events.recv().ok()
+    }
+
+    /// Returns the next event, if one already happened
+    pub fn try_recv(&self) -> Option<E> {
+        self.events.try_recv().ok()
+    }
+
+    /// Waits at most `timeout` for the next event
+    pub fn recv_timeout(&self, timeout: Duration) -> Option<E> {
+        self.events.recv_timeout(timeout).ok()
+    }
+
+    /// Stops watching
+    pub fn stop(mut self) {
+        self.shutdown();
+    }
+
+    fn shutdown(&mut self) {
+        self.stop.take();
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+        }
+    }
+}
+
+impl<E> Iterator for UnitWatcher<E> {
+    type Item = E;
+    fn next(&mut self) -> Option<Self::Item> {
+        self.recv()
+    }
+}
+
+impl<E> Drop for UnitWatcher<E> {
+    fn drop(&mut self) {
+        self.shutdown();
+    }
+}
+
+impl SystemCtl {
+    /// Watches the state of given `unit`, polling it every second.
+    ///
+    /// ```no_run
+    /// let ctl = systemctl::SystemCtl::default();
+    /// for change in ctl.watch("nginx.service").unwrap() {
+    ///     if change.new.active == systemctl::ActiveState::Failed {
+    ///         println!("{} failed at {:?}", change.unit, change.timestamp);
+    ///     }
+    /// }
+    /// ```
+    pub fn watch(&self, unit: &str) -> std::io::Result<UnitWatcher> {
+        self.watch_units(&[unit], WATCH_INTERVAL)
+    }
+
+    /// Watches the state of given `units`, polling them
+    /// (`systemctl show -p ActiveState,SubState`) every `interval`.
+    /// Initial states are queried before returning, only
+    /// later transitions are reported.
+    pub fn watch_units(&self, units: &[&str], interval: Duration) -> std::io::Result<UnitWatcher> {
+        let mut states = HashMap::with_capacity(units.len());
+        for unit in units {
+            states.insert(unit.to_string(), self.unit_state(unit)?);
+        }
+        let ctl = self.clone();
+        Ok(UnitWatcher::spawn(interval, move |events| {
+            for (unit, old) in states.iter_mut() {
+                // transient failures are retried on next poll
+                let Ok(new) = ctl.unit_state(unit) else {
+                    continue;
+                };
+                if let Some(change) = StateChange::detect(unit, old, new) {
+                    if events.send(change).is_err() {
+                        return false;
+                    }
+                }
+            }
+            true
+        }))
+    }
+
+    /// Queries the current state of given `unit`
+    pub(crate) fn unit_state(&self, unit: &str) -> std::io::Result<UnitState> {
+        let mut properties = self.show(unit, &["ActiveState", "SubState"])?;
+        Ok(UnitState::from_properties(&mut properties))
+    }
+}
+
+impl UnitState {
+    /// Takes the state out of `systemctl show` properties
+    pub(crate) fn from_properties(properties: &mut HashMap<String, String>) -> Self {
+        Self {
+            active: properties
+                .get("ActiveState")
+                .and_then(|state| ActiveState::from_str(state).ok())
+                .unwrap_or_default(),
+            sub: properties.remove("SubState").unwrap_or_default(),
+        }
+    }
+}
+
+impl StateChange {
+    /// Records the transition of `unit` from `old` to `new`, if any
+    pub(crate) fn detect(unit: &str, old: &mut UnitState, new: UnitState) -> Option<Self> {
+        if new == *old {
+            return None;
+        }
+        Some(Self {
+            unit: unit.to_string(),
+            old: std::mem::replace(old, new.clone()),
+            new,
+            timestamp: SystemTime::now(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{MockExecutor, MockResponse};
+
+    #[test]
+    fn test_watch_units() {
+        let mock = MockExecutor::new();
+        mock.on(
+            &["show"],
+            MockResponse::success("ActiveState=active\nSubState=running\n"),
+        );
+        let watcher = mock
+            .ctl()
+            .watch_units(&["test.service"], Duration::from_millis(10))
+            .unwrap();
+        assert!(watcher.recv_timeout(Duration::from_millis(100)).is_none());
+
+        mock.on(
+            &["show"],
+            MockResponse::success("ActiveState=failed\nSubState=failed\n"),
+        );
+        let change = watcher.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert_eq!(change.unit, "test.service");
+        assert_eq!(change.old.active, ActiveState::Active);
+        assert_eq!(change.old.sub, "running");
+        assert_eq!(change.new.active, ActiveState::Failed);
+        assert_eq!(change.new.sub, "failed");
+        watcher.stop();
+    }
+}